@@ -0,0 +1,49 @@
+// 配置文件：$XDG_CONFIG_HOME/rust_kill/config.toml，默认 ~/.config/rust_kill/config.toml
+//
+//   # 受保护的路径（glob），命中的目录及其子目录永远不会被删除
+//   protect = ["~/work/legacy", "**/vendor/**"]
+//   # 扫描时跳过的路径（glob），整个子树都不会被读取；相对路径相对于扫描根目录
+//   exclude = ["~/Library", "**/.git", "third_party", "tools/sdk"]
+use anyhow::Context;
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub protect: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl Config {
+    // 读取配置文件；未指定路径时使用默认位置，默认位置不存在时返回空配置
+    pub fn load(path: Option<&Path>) -> anyhow::Result<Self> {
+        let (path, explicit) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default()),
+            },
+        };
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound && !explicit => {
+                return Ok(Self::default())
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("读取配置文件失败: {}", path.display()))
+            }
+        };
+        toml::from_str(&text).with_context(|| format!("配置文件格式错误: {}", path.display()))
+    }
+}
+
+fn default_path() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("rust_kill").join("config.toml"))
+}
@@ -0,0 +1,119 @@
+use crate::models::{DeleteStatus, FileEntry};
+use crate::protect::Protection;
+use crate::quarantine::quarantine;
+use crate::trash::move_to_trash;
+use log::info;
+use rayon::prelude::*;
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+
+// 删除后端：直接删除、移入回收站或移入隔离区
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeleteBackend {
+    Remove,
+    Trash,
+    Quarantine,
+}
+
+// 删除单个目录，成功时返回最终状态，失败时返回错误信息字符串便于跨线程传递
+// 删除前先做安全检查，被拒绝时不做任何操作
+pub fn delete_path(
+    path: &str,
+    backend: DeleteBackend,
+    protection: &Protection,
+) -> Result<DeleteStatus, String> {
+    protection.check(Path::new(path))?;
+    match backend {
+        DeleteBackend::Remove => remove(Path::new(path))
+            .map(|_| DeleteStatus::Deleted)
+            .map_err(|e| e.to_string()),
+        DeleteBackend::Trash => move_to_trash(Path::new(path))
+            .map(|trashed| {
+                info!("已移入回收站: {:?} -> {:?}", path, trashed);
+                DeleteStatus::Trashed
+            })
+            .map_err(|e| e.to_string()),
+        DeleteBackend::Quarantine => quarantine(Path::new(path))
+            .map(|record| {
+                info!("已隔离: {:?} -> {:?}", path, record.staged_path);
+                DeleteStatus::Quarantined(record.id)
+            })
+            .map_err(|e| e.to_string()),
+    }
+}
+
+// 直接删除；符号链接只移除链接本身，不会删除它指向的目录
+fn remove(path: &Path) -> std::io::Result<()> {
+    if fs::symlink_metadata(path)?.file_type().is_symlink() {
+        // Windows 上指向目录的链接需要用 remove_dir 删除
+        fs::remove_file(path).or_else(|_| fs::remove_dir(path))
+    } else {
+        fs::remove_dir_all(path)
+    }
+}
+
+// 并行删除所有条目，返回带有删除结果的条目
+pub fn delete_entries(
+    entries: Vec<FileEntry>,
+    backend: DeleteBackend,
+    protection: &Protection,
+) -> Vec<FileEntry> {
+    entries
+        .into_par_iter()
+        .map(|mut entry| {
+            entry.delete_status = match delete_path(&entry.path, backend, protection) {
+                Ok(status) => {
+                    info!("删除成功: {:?}", entry.path);
+                    status
+                }
+                Err(e) => {
+                    info!("删除失败: {:?}: {}", entry.path, e);
+                    DeleteStatus::Failed(e)
+                }
+            };
+            entry
+        })
+        .collect()
+}
+
+// 删除结果：entries 中的下标和删除结果
+pub type DeleteResult = (usize, Result<DeleteStatus, String>);
+
+// TUI 的删除线程池，线程数固定为 CPU 核数，批量删除上百个目录时不会为每个目录单独创建线程
+pub struct DeletePool {
+    pool: rayon::ThreadPool,
+    backend: DeleteBackend,
+    protection: Arc<Protection>,
+    results: Sender<DeleteResult>,
+}
+
+impl DeletePool {
+    pub fn new(
+        backend: DeleteBackend,
+        protection: Arc<Protection>,
+        results: Sender<DeleteResult>,
+    ) -> Result<Self, rayon::ThreadPoolBuildError> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_cpus::get())
+            .thread_name(|i| format!("delete-{}", i))
+            .build()?;
+        Ok(Self {
+            pool,
+            backend,
+            protection,
+            results,
+        })
+    }
+
+    // 提交一个删除任务，完成后把结果按下标发回 UI 线程
+    pub fn submit(&self, index: usize, path: String) {
+        let backend = self.backend;
+        let protection = Arc::clone(&self.protection);
+        let results = self.results.clone();
+        self.pool.spawn(move || {
+            let _ = results.send((index, delete_path(&path, backend, &protection)));
+        });
+    }
+}
@@ -0,0 +1,63 @@
+// CACHEDIR.TAG：cargo、ccache、pip 等工具在缓存目录中写入的通用标记
+// https://bford.info/cachedir/
+use super::{Detector, RiskLevel};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+const TAG_FILE: &str = "CACHEDIR.TAG";
+const SIGNATURE: &[u8] = b"Signature: 8a477f597d28d172789f06886806bc55";
+
+// 不看目录名，只要目录中有合法的 CACHEDIR.TAG 就认为是缓存目录
+pub struct CacheDirTagDetector;
+
+impl Detector for CacheDirTagDetector {
+    fn name(&self) -> &str {
+        "cachedir"
+    }
+
+    fn description(&self) -> &str {
+        "带有 CACHEDIR.TAG 标记的缓存目录"
+    }
+
+    fn risk(&self) -> RiskLevel {
+        RiskLevel::Low
+    }
+
+    fn matches(&self, path: &Path, _dir_name: &str) -> bool {
+        has_valid_tag(path)
+    }
+}
+
+// 目录中的 CACHEDIR.TAG 是否以规范要求的签名开头
+pub fn has_valid_tag(dir: &Path) -> bool {
+    let Ok(file) = File::open(dir.join(TAG_FILE)) else {
+        return false;
+    };
+    let mut header = Vec::with_capacity(SIGNATURE.len());
+    file.take(SIGNATURE.len() as u64)
+        .read_to_end(&mut header)
+        .is_ok_and(|_| is_valid_signature(&header))
+}
+
+fn is_valid_signature(header: &[u8]) -> bool {
+    header == SIGNATURE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_must_match_exactly() {
+        assert!(is_valid_signature(
+            b"Signature: 8a477f597d28d172789f06886806bc55"
+        ));
+        assert!(!is_valid_signature(
+            b"Signature: 8a477f597d28d172789f06886806bc5"
+        ));
+        assert!(!is_valid_signature(
+            b"signature: 8a477f597d28d172789f06886806bc55"
+        ));
+    }
+}
@@ -0,0 +1,92 @@
+// Cargo 构建目录识别：只有确实由 cargo 生成的 target 目录才算可清理，
+// 同时支持 CARGO_TARGET_DIR 和 .cargo/config.toml 中的 build.target-dir
+use super::{cachedir, Detector, RiskLevel};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub struct CargoDetector;
+
+impl Detector for CargoDetector {
+    fn name(&self) -> &str {
+        "cargo"
+    }
+
+    fn description(&self) -> &str {
+        "Cargo 构建目录"
+    }
+
+    fn risk(&self) -> RiskLevel {
+        RiskLevel::Low
+    }
+
+    fn matches(&self, path: &Path, dir_name: &str) -> bool {
+        dir_name == "target" && is_cargo_target(path)
+    }
+
+    fn claims_name(&self, dir_name: &str) -> bool {
+        dir_name == "target"
+    }
+
+    fn relocated_dir(&self, project: &Path) -> Option<PathBuf> {
+        if !project.join("Cargo.toml").is_file() {
+            return None;
+        }
+        configured_target_dir(project)
+    }
+}
+
+// target 目录是否由 cargo 生成：同级存在 Cargo.toml，或目录内有 cargo 写入的
+// .rustc_info.json / 合法的 CACHEDIR.TAG
+fn is_cargo_target(dir: &Path) -> bool {
+    let sibling_manifest = dir
+        .parent()
+        .is_some_and(|parent| parent.join("Cargo.toml").is_file());
+    sibling_manifest || dir.join(".rustc_info.json").is_file() || cachedir::has_valid_tag(dir)
+}
+
+// 项目实际使用的构建目录（如果被配置到了别处）：CARGO_TARGET_DIR 优先，
+// 其次从项目目录向上查找 .cargo/config.toml 中的 build.target-dir
+fn configured_target_dir(project: &Path) -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("CARGO_TARGET_DIR").filter(|v| !v.is_empty()) {
+        // 与 cargo 一致，相对路径相对于当前工作目录
+        return std::path::absolute(dir).ok();
+    }
+    project.ancestors().find_map(config_target_dir)
+}
+
+// 读取 <dir>/.cargo/config(.toml)，相对路径相对于 .cargo 所在的目录
+fn config_target_dir(dir: &Path) -> Option<PathBuf> {
+    // 两个文件同时存在时 cargo 使用不带扩展名的旧文件
+    let text = ["config", "config.toml"]
+        .iter()
+        .find_map(|name| fs::read_to_string(dir.join(".cargo").join(name)).ok())?;
+    parse_target_dir(&text).map(|target_dir| dir.join(target_dir))
+}
+
+fn parse_target_dir(text: &str) -> Option<String> {
+    let config: toml::Table = text.parse().ok()?;
+    config
+        .get("build")?
+        .get("target-dir")?
+        .as_str()
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_target_dir_from_config() {
+        assert_eq!(
+            parse_target_dir("[build]\ntarget-dir = \"../shared-target\"\n"),
+            Some("../shared-target".to_string())
+        );
+        assert_eq!(
+            parse_target_dir("build.target-dir = \"out\"\n"),
+            Some("out".to_string())
+        );
+        assert_eq!(parse_target_dir("[build]\njobs = 4\n"), None);
+    }
+}
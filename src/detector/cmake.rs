@@ -0,0 +1,58 @@
+// CMake 构建目录识别：不看目录名，只认 out-of-source 构建目录。
+// in-source 构建时 CMakeCache.txt 就写在源码目录里，这样的目录绝不能当作清理目标
+use super::{Detector, RiskLevel};
+use std::path::Path;
+
+pub struct CMakeDetector;
+
+impl Detector for CMakeDetector {
+    fn name(&self) -> &str {
+        "cmake"
+    }
+
+    fn description(&self) -> &str {
+        "CMake 构建目录（含 CMakeCache.txt 和 CMakeFiles/，不含 CMakeLists.txt）"
+    }
+
+    fn risk(&self) -> RiskLevel {
+        RiskLevel::Medium
+    }
+
+    fn matches(&self, path: &Path, _dir_name: &str) -> bool {
+        is_cmake_build_dir(path)
+    }
+}
+
+// cmake 生成的构建目录同时有 CMakeCache.txt 和 CMakeFiles/；
+// 含 CMakeLists.txt 说明是源码目录（in-source 构建）
+fn is_cmake_build_dir(dir: &Path) -> bool {
+    dir.join("CMakeCache.txt").is_file()
+        && dir.join("CMakeFiles").is_dir()
+        && !dir.join("CMakeLists.txt").exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn in_source_build_is_not_a_target() {
+        let root = std::env::temp_dir().join(format!("rust_kill-cmake-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let project = root.join("proj");
+        let build = project.join("build");
+        for dir in [&project, &build] {
+            fs::create_dir_all(dir.join("CMakeFiles")).unwrap();
+            fs::write(dir.join("CMakeCache.txt"), "").unwrap();
+        }
+        fs::write(project.join("CMakeLists.txt"), "").unwrap();
+
+        assert!(!is_cmake_build_dir(&project));
+        assert!(is_cmake_build_dir(&build));
+        fs::remove_dir(build.join("CMakeFiles")).unwrap();
+        assert!(!is_cmake_build_dir(&build));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,333 @@
+// 各生态构建产物目录的识别规则：每个检测器根据目录名、父目录中的项目文件
+// 和目录内的标记文件判断一个目录是否可以安全清理，按 --preset 组合使用
+pub mod cachedir;
+pub mod cargo;
+pub mod cmake;
+
+use crate::models::Preset;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// 删除风险：Low 为纯缓存，构建时自动重新生成；Medium 需要手动重建（如虚拟环境）；
+// High 为只按目录名匹配、无法确认内容的自定义目录
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl RiskLevel {
+    // 机器可读的名称，与 JSON 序列化保持一致
+    pub fn key(&self) -> &'static str {
+        match self {
+            RiskLevel::Low => "low",
+            RiskLevel::Medium => "medium",
+            RiskLevel::High => "high",
+        }
+    }
+}
+
+impl std::fmt::Display for RiskLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RiskLevel::Low => write!(f, "低"),
+            RiskLevel::Medium => write!(f, "中"),
+            RiskLevel::High => write!(f, "高"),
+        }
+    }
+}
+
+pub trait Detector: Send + Sync {
+    // 检测器名称，写入 FileEntry::matched_pattern
+    fn name(&self) -> &str;
+
+    fn description(&self) -> &str;
+
+    fn risk(&self) -> RiskLevel;
+
+    // path 是否为该生态的构建产物目录，dir_name 为 path 的最后一段
+    fn matches(&self, path: &Path, dir_name: &str) -> bool;
+
+    // 项目把构建目录配置到了别处时返回该目录（如 cargo 的 build.target-dir）
+    fn relocated_dir(&self, _project: &Path) -> Option<PathBuf> {
+        None
+    }
+
+    // -d 指定的目录名是否由该检测器负责，是则用它代替只按名称匹配的自定义检测器
+    fn claims_name(&self, _dir_name: &str) -> bool {
+        false
+    }
+}
+
+// 通用检测器：目录名 + 父目录中的项目文件 + 目录内的标记文件
+struct MarkerDetector {
+    name: &'static str,
+    description: &'static str,
+    risk: RiskLevel,
+    // 目录名之一；为空时不限制目录名
+    dir_names: &'static [&'static str],
+    // 父目录中需要存在其中之一（支持 *.ext）；为空时不要求
+    parent_markers: &'static [&'static str],
+    // 目录内需要存在其中之一；为空时不要求
+    dir_markers: &'static [&'static str],
+}
+
+impl Detector for MarkerDetector {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn description(&self) -> &str {
+        self.description
+    }
+
+    fn risk(&self) -> RiskLevel {
+        self.risk
+    }
+
+    fn matches(&self, path: &Path, dir_name: &str) -> bool {
+        if !self.dir_names.is_empty() && !self.dir_names.contains(&dir_name) {
+            return false;
+        }
+        if !self.parent_markers.is_empty() {
+            let Some(parent) = path.parent() else {
+                return false;
+            };
+            if !has_any_marker(parent, self.parent_markers) {
+                return false;
+            }
+        }
+        self.dir_markers.is_empty() || has_any_marker(path, self.dir_markers)
+    }
+
+    // 只有不需要任何标记文件的检测器才等同于按名称匹配
+    fn claims_name(&self, dir_name: &str) -> bool {
+        self.dir_names.contains(&dir_name)
+            && self.parent_markers.is_empty()
+            && self.dir_markers.is_empty()
+    }
+}
+
+// dir 中是否存在任一标记文件；"*.ext" 形式需要列出目录按扩展名匹配
+fn has_any_marker(dir: &Path, markers: &[&str]) -> bool {
+    let (globs, names): (Vec<&str>, Vec<&str>) = markers.iter().partition(|m| m.starts_with("*."));
+    if names.iter().any(|name| dir.join(name).exists()) {
+        return true;
+    }
+    if globs.is_empty() {
+        return false;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        globs.iter().any(|glob| file_name.ends_with(&glob[1..]))
+    })
+}
+
+// -d/--dir 指定的自定义目录名，只按名称精确匹配
+struct NameDetector {
+    name: String,
+}
+
+impl Detector for NameDetector {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        "通过 -d/--dir 指定的目录名"
+    }
+
+    fn risk(&self) -> RiskLevel {
+        RiskLevel::High
+    }
+
+    fn matches(&self, _path: &Path, dir_name: &str) -> bool {
+        dir_name == self.name
+    }
+}
+
+const GRADLE_PROJECT: &[&str] = &[
+    "build.gradle",
+    "build.gradle.kts",
+    "settings.gradle",
+    "settings.gradle.kts",
+];
+const DOTNET_PROJECT: &[&str] = &["*.csproj", "*.fsproj", "*.vbproj", "*.sln"];
+const NEXT_CONFIG: &[&str] = &["next.config.js", "next.config.mjs", "next.config.ts"];
+const NUXT_CONFIG: &[&str] = &["nuxt.config.js", "nuxt.config.mjs", "nuxt.config.ts"];
+
+// 某个预设包含的内置检测器
+fn builtin(preset: Preset) -> Vec<Arc<dyn Detector>> {
+    let marker = |detector: MarkerDetector| Arc::new(detector) as Arc<dyn Detector>;
+    match preset {
+        Preset::Node => vec![marker(MarkerDetector {
+            name: "npm",
+            description: "npm / yarn / pnpm 安装的依赖",
+            risk: RiskLevel::Low,
+            dir_names: &["node_modules"],
+            parent_markers: &[],
+            dir_markers: &[],
+        })],
+        Preset::Rust => vec![Arc::new(cargo::CargoDetector)],
+        Preset::Python => vec![
+            marker(MarkerDetector {
+                name: "pycache",
+                description: "Python 字节码缓存",
+                risk: RiskLevel::Low,
+                dir_names: &["__pycache__"],
+                parent_markers: &[],
+                dir_markers: &[],
+            }),
+            marker(MarkerDetector {
+                name: "venv",
+                description: "Python 虚拟环境，删除后需要重新安装依赖",
+                risk: RiskLevel::Medium,
+                dir_names: &[".venv"],
+                parent_markers: &[],
+                dir_markers: &["pyvenv.cfg"],
+            }),
+            marker(MarkerDetector {
+                name: "tox",
+                description: "tox 测试环境",
+                risk: RiskLevel::Low,
+                dir_names: &[".tox"],
+                parent_markers: &[],
+                dir_markers: &[],
+            }),
+            marker(MarkerDetector {
+                name: "mypy",
+                description: "mypy 类型检查缓存",
+                risk: RiskLevel::Low,
+                dir_names: &[".mypy_cache"],
+                parent_markers: &[],
+                dir_markers: &[],
+            }),
+        ],
+        Preset::Jvm => vec![
+            marker(MarkerDetector {
+                name: "gradle",
+                description: "Gradle 构建输出和项目缓存",
+                risk: RiskLevel::Low,
+                dir_names: &["build", ".gradle"],
+                parent_markers: GRADLE_PROJECT,
+                dir_markers: &[],
+            }),
+            marker(MarkerDetector {
+                name: "maven",
+                description: "Maven 构建输出",
+                risk: RiskLevel::Low,
+                dir_names: &["target"],
+                parent_markers: &["pom.xml"],
+                dir_markers: &[],
+            }),
+        ],
+        Preset::Dotnet => vec![marker(MarkerDetector {
+            name: "dotnet",
+            description: ".NET 编译输出（bin / obj）",
+            risk: RiskLevel::Medium,
+            dir_names: &["bin", "obj"],
+            parent_markers: DOTNET_PROJECT,
+            dir_markers: &[],
+        })],
+        Preset::Web => vec![
+            marker(MarkerDetector {
+                name: "next",
+                description: "Next.js 构建缓存",
+                risk: RiskLevel::Low,
+                dir_names: &[".next"],
+                parent_markers: NEXT_CONFIG,
+                dir_markers: &[],
+            }),
+            marker(MarkerDetector {
+                name: "nuxt",
+                description: "Nuxt 构建缓存和输出",
+                risk: RiskLevel::Low,
+                dir_names: &[".nuxt", ".output"],
+                parent_markers: NUXT_CONFIG,
+                dir_markers: &[],
+            }),
+        ],
+        Preset::Cmake => vec![Arc::new(cmake::CMakeDetector)],
+        Preset::All => Preset::ALL.iter().flat_map(|&p| builtin(p)).collect(),
+    }
+}
+
+// 一次扫描启用的检测器集合
+#[derive(Clone)]
+pub struct Registry {
+    detectors: Vec<Arc<dyn Detector>>,
+}
+
+impl Registry {
+    // 按预设和 -d 指定的目录名组合检测器；都未指定时使用 rust + node
+    // -d 的目录名有对应的内置检测器时使用内置的（如 target 使用 cargo 检测器，
+    // 避免把任意名为 target 的目录当作构建目录）
+    pub fn new(presets: &[Preset], names: &[String]) -> Self {
+        let mut presets = presets.to_vec();
+        if presets.is_empty() && names.is_empty() {
+            presets = vec![Preset::Rust, Preset::Node];
+        }
+        let mut detectors: Vec<Arc<dyn Detector>> = presets.into_iter().flat_map(builtin).collect();
+        for name in names {
+            let detector = builtin(Preset::All)
+                .into_iter()
+                .find(|d| d.claims_name(name))
+                .unwrap_or_else(|| Arc::new(NameDetector { name: name.clone() }));
+            if !detectors.iter().any(|d| d.name() == detector.name()) {
+                detectors.push(detector);
+            }
+        }
+        Self { detectors }
+    }
+
+    // 启用 CACHEDIR.TAG 检测：放在最后，已被具体生态识别的目录仍归属原检测器
+    pub fn enable_cachedir_tag(&mut self) {
+        self.detectors.push(Arc::new(cachedir::CacheDirTagDetector));
+    }
+
+    // 返回第一个认为 path 是构建产物的检测器
+    pub fn detect(&self, path: &Path, dir_name: &str) -> Option<Arc<dyn Detector>> {
+        self.detectors
+            .iter()
+            .find(|d| d.matches(path, dir_name))
+            .cloned()
+    }
+
+    // 按名称查找检测器
+    pub fn get(&self, name: &str) -> Option<&dyn Detector> {
+        self.detectors
+            .iter()
+            .find(|d| d.name() == name)
+            .map(|d| d.as_ref())
+    }
+
+    // 项目被重新配置的构建目录，以及对应的检测器
+    pub fn relocated_dirs(&self, project: &Path) -> Vec<(Arc<dyn Detector>, PathBuf)> {
+        self.detectors
+            .iter()
+            .filter_map(|d| d.relocated_dir(project).map(|dir| (Arc::clone(d), dir)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_detector_matches_exact_name_only() {
+        let registry = Registry::new(&[], &["node_modules".to_string()]);
+        let path = Path::new("/tmp/app/node_modules");
+        assert!(registry.detect(path, "node_modules").is_some());
+        let backup = Path::new("/tmp/app/my_node_modules_backup");
+        assert!(registry.detect(backup, "my_node_modules_backup").is_none());
+    }
+}
@@ -1,252 +1,153 @@
-use super::models::FileEntry;
+use super::models::{FileEntry, ProjectActivity};
 use super::utils::{human_readable_size, progress_bar_init};
+use crate::detector::{Detector, Registry};
+use crate::gitignore;
 use crate::models::DeleteStatus::NotDeleted;
+use crate::models::SizeMode;
+use crate::utils::{canonicalize_parent, expand_tilde};
 use crate::ScanStatus;
 use comfy_table::{Cell, ContentArrangement, Table};
+use globset::{Glob, GlobSet, GlobSetBuilder};
 
-use indicatif::ProgressBar;
+use jwalk::{DirEntryIter, Parallelism, WalkDirGeneric};
 use log::info;
-use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::Sender;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
 
-pub fn calculate_dir_size(
-    path: &Path,
-    human_readable: bool,
-    main_pb: &ProgressBar,
-    parallel: bool,
-) -> (u64, String) {
-    // ✅ 设置当前计算的路径
-    main_pb.set_message(format!("计算 {}...", path.display()));
-    // 关键：用 Arc 包装，实现线程安全共享
-    let pb_arc = Arc::new(main_pb.clone());
-
-    let total = if parallel {
-        // inner_calculate_parallel(path, &pb_arc, 0)
-        inner_calculate_dynamic(path, &pb_arc, 0)
-    } else {
-        inner_calculate_serial(path, &pb_arc)
-    };
-
-    let converted = if human_readable {
-        human_readable_size(total)
-    } else {
-        total.to_string()
-    };
-    (total, converted)
-}
-// 动态并行：根据目录复杂度决定是否并行
-fn inner_calculate_dynamic(path: &Path, pb: &Arc<ProgressBar>, depth: usize) -> u64 {
-    if depth > 0 && depth <= 2 {
-        // 只显示前2层，避免消息刷新太频繁
-        pb.set_message(format!("计算 {}...", path.display()));
-    }
-    match fs::read_dir(path) {
-        Ok(entries) => {
-            let entries_vec: Vec<_> = entries.collect();
-            //根据深度决定tick频率
-            let tick_freq = if depth == 0 {
-                50
-            } else if depth < 3 {
-                100
-            } else {
-                200
-            };
-            // 收集条目并统计信息
-            let items: Vec<_> = entries_vec
-                .into_iter()
-                .enumerate()
-                .filter_map(|(i, e)| {
-                    // 批量tick
-                    if i % tick_freq == 0 {
-                        pb.tick();
-                    }
-
-                    let entry = e.ok()?;
-                    let metadata = entry.metadata().ok()?;
-                    Some((entry.path(), metadata))
-                })
-                .collect();
+// 扫描选项，在各个扫描入口之间共享
+#[derive(Clone)]
+pub struct ScanOptions {
+    pub detectors: Registry,
+    // 只报告位于 git 工作区内、且被该仓库忽略规则忽略的目录
+    pub gitignored_only: bool,
+    // 扫描时跳过的路径，命中的目录整个子树都不会被读取
+    pub exclude: GlobSet,
+    // 扫描以 . 开头的隐藏目录（.git 始终跳过）
+    pub hidden: bool,
+    // 条目的 size_raw 使用表观大小还是实际占用的磁盘空间
+    pub size_mode: SizeMode,
+    // 不跨越文件系统：发现和统计大小时都跳过挂载在扫描根目录之下的其他文件系统
+    pub one_file_system: bool,
+    // 查找目标时跟随指向目录的符号链接；统计大小时始终不跟随
+    pub follow_symlinks: bool,
+}
 
-            // 动态决策：是否使用并行
-            let use_parallel = should_use_parallel(&items, depth);
-
-            if use_parallel {
-                // 并行处理
-                items
-                    .into_par_iter()
-                    .map(|(item_path, metadata)| {
-                        if metadata.is_dir() {
-                            inner_calculate_dynamic(&item_path, pb, depth + 1)
-                        } else {
-                            metadata.len()
-                        }
-                    })
-                    .sum()
-            } else {
-                // 串行处理
-                let mut total = 0;
-                for (item_path, metadata) in items {
-                    if metadata.is_dir() {
-                        total += inner_calculate_dynamic(&item_path, pb, depth + 1);
-                    } else {
-                        total += metadata.len();
-                    }
-                }
-                total
-            }
-        }
-        Err(e) => {
-            eprintln!("无法读取目录 {}: {}", path.display(), e);
-            0
-        }
+// 构建 --exclude / 配置文件中的排除规则，规则与 .gitignore 一致：
+// 不含 / 的模式按目录名匹配；"/"、"~/"（家目录）或 "**" 开头的按绝对路径匹配；
+// 其余含 / 的模式（如 third_party/sdk）相对于扫描根目录 root
+pub fn build_exclude(patterns: &[String], root: &Path) -> Result<GlobSet, globset::Error> {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        // 结尾的 / 只表示目录，排除规则本来就只作用于目录
+        let pattern = pattern.trim_end_matches('/');
+        let pattern = if !pattern.contains('/') {
+            format!("**/{}", pattern)
+        } else if pattern.starts_with('/') || pattern.starts_with("~/") || pattern.starts_with("**")
+        {
+            expand_tilde(pattern)
+        } else {
+            let relative = pattern.trim_start_matches("./");
+            format!(
+                "{}/{}",
+                globset::escape(&root.to_string_lossy()).trim_end_matches('/'),
+                relative
+            )
+        };
+        builder.add(Glob::new(&pattern)?);
     }
+    builder.build()
 }
-// 智能决策：是否使用并行
-fn should_use_parallel(items: &[(PathBuf, std::fs::Metadata)], depth: usize) -> bool {
-    // 如果深度太大，直接返回false
-    if depth > 10 {
-        return false;
-    }
 
-    // 统计子目录数量
-    let dir_count = items.iter().filter(|(_, m)| m.is_dir()).count();
-    // 策略1：根据子目录数量决定
-    //子目录越多，越应该并行
-    if dir_count > 8 {
-        return true;
-    }
+// 大小与最新修改时间一起统计
+#[derive(Debug, Clone, Copy, Default)]
+struct DirStats {
+    // 文件长度之和
+    apparent: u64,
+    // 分配的磁盘空间，包括目录自身占用的块
+    disk: u64,
+    // 子树中最新的 mtime
+    latest: Option<SystemTime>,
+}
 
-    // 策略2：根据总项数决定
-    // 项数越多，越应该并行
-    if items.len() > 100 {
-        return true;
+impl DirStats {
+    fn merge(self, other: DirStats) -> DirStats {
+        DirStats {
+            apparent: self.apparent + other.apparent,
+            disk: self.disk + other.disk,
+            latest: self.latest.max(other.latest),
+        }
     }
+}
 
-    // 策略3：根据深度调整
-    // 深度越大，越应该谨慎并行
-    if depth > 5 {
-        return dir_count > 4; // 只有子目录多才并行
-    }
+// 文件所在设备的编号，用于判断是否跨越了文件系统
+#[cfg(unix)]
+fn device_id(metadata: &fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.dev())
+}
 
-    // 策略4：混合模式
-    // 浅层大胆并行，深层保守
-    depth < 3 || (depth < 6 && dir_count > 2)
+#[cfg(not(unix))]
+fn device_id(_metadata: &fs::Metadata) -> Option<u64> {
+    None
 }
 
-// 串行版本：用于深度过大或小目录
-fn inner_calculate_serial(path: &Path, pb: &Arc<ProgressBar>) -> u64 {
-    pb.set_message(format!("计算 {}...", path.display()));
-    let mut total = 0;
-    if let Ok(entries) = fs::read_dir(path) {
-        for entry in entries.flatten() {
-            pb.tick();
-            if let Ok(metadata) = entry.metadata() {
-                if metadata.is_dir() {
-                    total += inner_calculate_serial(&entry.path(), pb);
-                } else {
-                    total += metadata.len();
-                }
-            }
-        }
-    }
-    total
+// 实际占用的磁盘空间：st_blocks 以 512 字节为单位，与文件系统块大小无关
+#[cfg(unix)]
+fn allocated_size(metadata: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.blocks() * 512
 }
 
-pub fn list_directory(path: &Path) -> Vec<FileEntry> {
-    let entries = match fs::read_dir(path) {
-        Ok(entries) => entries,
-        Err(e) => {
-            eprintln!("ls: cannot access '{}': {}", path.display(), e);
-            return Vec::new();
-        }
-    };
-    let mut files: Vec<String> = Vec::new();
+#[cfg(not(unix))]
+fn allocated_size(metadata: &fs::Metadata) -> u64 {
+    metadata.len()
+}
 
-    for entry in entries.flatten() {
-        let file_name = entry.file_name().to_string_lossy().to_string();
-        files.push(file_name);
-    }
+const PROJECT_MANIFESTS: &[&str] = &["Cargo.toml", "package.json"];
 
-    files.sort();
-    let scan_pb = progress_bar_init(None).unwrap();
+// 目录中是否直接包含项目文件（Cargo.toml / package.json）
+fn is_project(dir: &Path) -> bool {
+    PROJECT_MANIFESTS.iter().any(|m| dir.join(m).is_file())
+}
 
-    let mut entries = Vec::new(); // 新增存储条目信息的结构
+pub fn list_directory(path: &Path, options: &ScanOptions) -> Vec<FileEntry> {
+    if let Err(e) = fs::read_dir(path) {
+        eprintln!("ls: cannot access '{}': {}", path.display(), e);
+        return Vec::new();
+    }
 
     let process_pb = progress_bar_init(None).unwrap(); // 修改为不传入具体数值
     process_pb.set_message("处理中..."); // 设置固定提示信息
-    let pb_arc = Arc::new(&process_pb);
-    for (_i, file) in files.iter().enumerate() {
-        process_pb.tick();
-        let file_path = path.join(&file);
-        let name = String::from("node_modules");
-        // if name {
-        let metadata = match file_path.metadata() {
-            Ok(m) => m,
-            Err(e) => {
-                eprintln!("ls: cannot access '{}': {}", file_path.display(), e);
-                continue;
-            }
-        };
-        if metadata.is_dir() {
-            // 如果是目录，是否跟要搜索的名称匹配
-            if !file.contains(&name) {
-                // 使用并行版本
-                calculate_dir_size_parallel(
-                    file_path,
-                    true,
-                    Arc::clone(&pb_arc), // 克隆 Arc
-                    &name,
-                    &mut entries,
-                );
-                // info!("其他的");
-                continue;
-            }
-        } else {
-            continue;
-        }
-        // }
-        let metadata = match file_path.metadata() {
-            Ok(m) => m,
-            Err(e) => {
-                eprintln!("ls: cannot access '{}': {}", file_path.display(), e);
-                continue;
-            }
-        };
-
-        let (size_display, size_raw) = (human_readable_size(metadata.len()), metadata.len());
-        let entry = FileEntry {
-            file_type: if metadata.is_dir() { 'd' } else { '-' },
-            permissions: format!(
-                "{}-{}-{}",
-                if metadata.permissions().readonly() {
-                    "r"
-                } else {
-                    " "
-                },
-                "w",
-                "x"
-            ),
-            size_display,
-            size_raw,
-            path: match file_path.canonicalize() {
-                Ok(canonical_path) => get_canonical_path(&canonical_path),
-                Err(_e) => {
-                    // eprintln!("获取绝对路径失败: {}", e);
-                    file_path.to_string_lossy().into_owned()
-                }
-            },
-            delete_status: NotDeleted,
-        };
-        // info!("添加条目: {:?}", entry);
-        entries.push(entry);
-    }
+    let ctx = ScanContext::new(options, path);
+    let mut collected = Collected::default(); // 新增存储条目信息的结构
+    scan_tree(
+        path,
+        &ctx,
+        |current, _, _| process_pb.set_message(format!("计算 {}...", current.display())),
+        |event| {
+            process_pb.tick();
+            collected.push(event);
+        },
+    );
 
     process_pb.finish_and_clear();
+    let entries = collected.finish();
+    print_entries_table(&entries);
+    entries // 返回收集到的条目
+}
+
+// 以表格形式打印条目及汇总信息
+pub fn print_entries_table(entries: &[FileEntry]) {
     let mut sum_size = 0;
-    for entry in &entries {
+    for entry in entries {
         sum_size += entry.size_raw; // 使用第4个字段的原始大小
     }
 
@@ -258,6 +159,7 @@ pub fn list_directory(path: &Path) -> Vec<FileEntry> {
             Cell::new("权限").add_attribute(comfy_table::Attribute::Bold),
             Cell::new("大小").add_attribute(comfy_table::Attribute::Bold),
             Cell::new("路径").add_attribute(comfy_table::Attribute::Bold),
+            Cell::new("状态").add_attribute(comfy_table::Attribute::Bold),
         ])
         .load_preset(comfy_table::presets::UTF8_FULL)
         .apply_modifier(comfy_table::modifiers::UTF8_ROUND_CORNERS);
@@ -266,11 +168,12 @@ pub fn list_directory(path: &Path) -> Vec<FileEntry> {
         let file_path = &entry.path;
 
         table.add_row(vec![
-            Cell::new(&entry.file_type.to_string())
+            Cell::new(entry.file_type.to_string())
                 .set_alignment(comfy_table::CellAlignment::Center),
             Cell::new(entry.permissions.replace('-', "")),
             Cell::new(&entry.size_display),
             Cell::new(file_path),
+            Cell::new(&entry.delete_status),
         ]);
     }
 
@@ -282,88 +185,679 @@ pub fn list_directory(path: &Path) -> Vec<FileEntry> {
         human_readable_size(sum_size)
     );
     println!("└{:─^33}┘", "");
+}
 
-    scan_pb.finish_and_clear(); // 完成后清理进度条
-    entries // 返回收集到的条目
+// 扫描产出的消息：目标目录统计完大小就立即产出条目，
+// 所属项目遍历完成后再单独产出项目的活跃时间
+#[derive(Debug, Clone)]
+pub enum ScanEvent {
+    Entry(FileEntry),
+    Activity(ProjectActivity),
+}
+
+// 收集扫描产出的全部消息，扫描结束后为条目补全项目的活跃时间
+#[derive(Default)]
+struct Collected {
+    entries: Vec<FileEntry>,
+    activity: HashMap<String, Option<SystemTime>>,
 }
 
-// 搜索文件
-fn calculate_dir_size_parallel(
-    file_path: PathBuf,
-    human_readable: bool,
-    pb: Arc<&ProgressBar>, // 改为 Arc
-    name: &str,
-    entries: &mut Vec<FileEntry>,
+impl Collected {
+    fn push(&mut self, event: ScanEvent) {
+        match event {
+            ScanEvent::Entry(entry) => self.entries.push(entry),
+            ScanEvent::Activity(project) => {
+                self.activity
+                    .insert(project.project_path, project.project_activity);
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<FileEntry> {
+        for entry in &mut self.entries {
+            if let Some(&latest) = self.activity.get(&entry.project_path) {
+                entry.set_activity(latest);
+            }
+        }
+        self.entries
+    }
+}
+
+// 无界面扫描：在后台线程扫描，用进度条展示状态，返回补全了项目活跃时间的全部条目
+pub fn scan_directory(path: &Path, options: &ScanOptions) -> Vec<FileEntry> {
+    let mut collected = Collected::default();
+    scan_directory_streaming(path, options, |event| collected.push(event));
+    collected.finish()
+}
+
+// 无界面流式扫描：每发现一个条目、每个项目遍历完成都立即回调，不等待扫描结束
+pub fn scan_directory_streaming<F: FnMut(ScanEvent)>(
+    path: &Path,
+    options: &ScanOptions,
+    mut on_event: F,
 ) {
-    let sub_entries = match fs::read_dir(&file_path) {
-        Ok(entries) => entries,
-        Err(e) => {
-            eprintln!("ls: cannot access '{}': {}", file_path.display(), e);
-            return;
+    let (status_tx, status_rx) = mpsc::channel::<ScanStatus>();
+    let (entries_tx, entries_rx) = mpsc::channel::<ScanEvent>();
+
+    let path_clone = path.to_path_buf();
+    let options = options.clone();
+    let handle = thread::spawn(move || {
+        scan_directory_with_progress(&path_clone, &options, &status_tx, &entries_tx);
+    });
+
+    let pb = progress_bar_init(None).unwrap();
+    pb.enable_steady_tick(Duration::from_millis(100));
+    // 条目通道在扫描线程结束时关闭
+    for event in entries_rx {
+        if let ScanEvent::Entry(entry) = &event {
+            pb.set_message(format!("发现 {}", entry.path));
         }
-    };
+        on_event(event);
+    }
+    let _ = handle.join();
+    pb.finish_and_clear();
 
-    // 收集所有需要处理的目录
-    let dirs_to_process: Vec<_> = sub_entries
-        .filter_map(|e| e.ok())
-        .filter_map(|e| {
-            let name = e.file_name().to_string_lossy().to_string();
-            if name.starts_with('.') {
-                return None;
+    // 只关心完成状态中的排除数和跳过的挂载点
+    for status in status_rx.try_iter() {
+        if let ScanStatus::Completed {
+            excluded,
+            skipped_mounts,
+            ..
+        } = status
+        {
+            if excluded > 0 {
+                eprintln!("已按排除规则跳过 {} 个目录", excluded);
             }
-            let metadata = e.metadata().ok()?;
-            if !metadata.is_dir() {
-                return None;
+            for mount in skipped_mounts {
+                eprintln!("已跳过其他文件系统的挂载点: {}", mount);
             }
-            Some((e.path(), name))
+        }
+    }
+}
+
+// 一次扫描共享的上下文：扫描选项、已报告的目录和遍历用的线程池
+struct ScanContext {
+    options: ScanOptions,
+    // 已报告目录的规范化路径，工作区成员共享同一个构建目录时只报告一次
+    reported: Mutex<HashSet<PathBuf>>,
+    // 已命中、但还没开始读取内容的目标目录；读取时据此切换为统计大小
+    pending: Mutex<HashSet<PathBuf>>,
+    // 被排除规则跳过的目录数
+    excluded: AtomicUsize,
+    // --one-file-system 时扫描根目录所在的设备号，以及因此跳过的挂载点
+    root_dev: Option<u64>,
+    skipped_mounts: Mutex<Vec<PathBuf>>,
+    // 规范化后的扫描根目录，以及 --follow-symlinks 时已经跟随过的链接目标
+    root: PathBuf,
+    // 传入的扫描根目录，遍历产出的路径以它开头（可能是相对路径）
+    base: PathBuf,
+    followed: Mutex<HashSet<PathBuf>>,
+    pool: Arc<ThreadPool>,
+}
+
+impl ScanContext {
+    fn new(options: &ScanOptions, root: &Path) -> Arc<Self> {
+        let pool = ThreadPoolBuilder::new()
+            .thread_name(|i| format!("scan-{}", i))
+            .build()
+            .expect("创建扫描线程池失败");
+        Arc::new(Self {
+            options: options.clone(),
+            reported: Mutex::new(HashSet::new()),
+            pending: Mutex::new(HashSet::new()),
+            excluded: AtomicUsize::new(0),
+            root_dev: options
+                .one_file_system
+                .then(|| fs::metadata(root).ok().and_then(|m| device_id(&m)))
+                .flatten(),
+            skipped_mounts: Mutex::new(Vec::new()),
+            root: root.canonicalize().unwrap_or_else(|_| root.to_path_buf()),
+            base: root.to_path_buf(),
+            followed: Mutex::new(HashSet::new()),
+            pool: Arc::new(pool),
         })
-        .collect();
-
-    // 并行处理每个子目录
-    let results: Vec<Vec<FileEntry>> = dirs_to_process
-        .into_par_iter()
-        .map(|(sub_path, sub_name)| {
-            pb.tick();
-            let mut local_entries = Vec::new();
-            if sub_name.contains(name) {
-                // 匹配：计算大小
-                let (raw, converted) = calculate_dir_size(&sub_path, human_readable, &pb, true);
-                local_entries.push(FileEntry {
-                    file_type: 'd',
-                    permissions: "rwx".to_string(),
-                    size_display: converted,
-                    size_raw: raw,
-                    path: get_canonical_path(&sub_path),
-                    delete_status: NotDeleted,
-                });
-                info!(
-                    "子目录: {:?},name:{:?},local_entries:{:?}",
-                    sub_name, name, local_entries
-                );
-            // continue;
-            } else {
-                calculate_dir_size_parallel(
-                    sub_path,
-                    human_readable,
-                    Arc::clone(&pb),
-                    name,
-                    &mut local_entries,
-                );
-                // info!("进入");
+    }
+
+    // 判断目录是否为清理目标，返回命中的检测器
+    fn match_dir(&self, path: &Path, dir_name: &str) -> Option<Arc<dyn Detector>> {
+        let detector = self.options.detectors.detect(path, dir_name)?;
+        (self.allowed(path) && self.claim(path)).then_some(detector)
+    }
+
+    // 项目把构建目录配置到了别处时返回这些目录；同一个目录只返回一次。
+    // 扫描根目录之外的目录删除时会被安全检查拒绝，不作为目标
+    fn relocated_dirs(&self, project: &Path) -> Vec<(Arc<dyn Detector>, PathBuf)> {
+        self.options
+            .detectors
+            .relocated_dirs(project)
+            .into_iter()
+            .filter(|(_, dir)| {
+                dir.is_dir()
+                    && self.within_root(dir)
+                    && !self.is_excluded(dir)
+                    && !self.crosses_mount(dir)
+                    && self.allowed(dir)
+                    && self.claim(dir)
+            })
+            .collect()
+    }
+
+    // path 规范化后是否位于扫描根目录之内
+    fn within_root(&self, path: &Path) -> bool {
+        let within = path
+            .canonicalize()
+            .is_ok_and(|canonical| canonical.starts_with(&self.root) && canonical != self.root);
+        if !within {
+            info!("跳过扫描根目录之外的构建目录: {:?}", path);
+        }
+        within
+    }
+
+    // --one-file-system 时 path 是否位于其他文件系统上，是则记录为跳过的挂载点
+    fn crosses_mount(&self, path: &Path) -> bool {
+        let Some(root_dev) = self.root_dev else {
+            return false;
+        };
+        let crosses = fs::metadata(path)
+            .ok()
+            .and_then(|m| device_id(&m))
+            .is_some_and(|dev| dev != root_dev);
+        if crosses {
+            self.skipped_mounts
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push(path.to_path_buf());
+        }
+        crosses
+    }
+
+    // 是否进入符号链接指向的目录：指向扫描根目录之内（会按原路径遍历）、
+    // 指向根目录的上级（形成循环）或已经跟随过的目标都不再进入
+    fn follow_link(&self, path: &Path) -> bool {
+        let Ok(target) = path.canonicalize() else {
+            return false;
+        };
+        if target.starts_with(&self.root) || self.root.starts_with(&target) {
+            return false;
+        }
+        self.followed
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(target)
+    }
+
+    // 未指定 --hidden 时跳过所有隐藏目录；.git 内部不会有构建产物，始终跳过
+    fn skip_hidden(&self, name: &str) -> bool {
+        name == ".git" || (!self.options.hidden && name.starts_with('.'))
+    }
+
+    // 命中排除规则的目录不再读取，并计入排除数。
+    // 按绝对路径匹配：扫描根目录为相对路径（默认 "."）时，"~/" 或 "/" 开头的模式也能生效
+    fn is_excluded(&self, path: &Path) -> bool {
+        let absolute = match path.strip_prefix(&self.base) {
+            Ok(relative) => self.root.join(relative),
+            Err(_) => path.to_path_buf(),
+        };
+        let excluded = self.options.exclude.is_match(absolute);
+        if excluded {
+            self.excluded.fetch_add(1, Ordering::Relaxed);
+        }
+        excluded
+    }
+
+    // 安全模式下的额外过滤
+    fn allowed(&self, path: &Path) -> bool {
+        !self.options.gitignored_only || gitignore::is_ignored(path)
+    }
+
+    // 记录为已报告，首次出现时返回 true；符号链接按链接本身的位置记录
+    fn claim(&self, path: &Path) -> bool {
+        let key = canonicalize_parent(path).unwrap_or_else(|_| path.to_path_buf());
+        self.reported
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key)
+    }
+}
+
+// 遍历时附加在每个条目上的状态，在 jwalk 的工作线程中读取目录后填写
+#[derive(Default)]
+struct EntryState {
+    // 命中的检测器：该目录是清理目标
+    detector: Option<Arc<dyn Detector>>,
+    // 以该目录为项目时，配置到别处的构建目录
+    relocated: Vec<(Arc<dyn Detector>, PathBuf)>,
+    // 目标目录本身是符号链接：只统计、删除链接本身
+    symlink: bool,
+    // 目标目录之外的目录直接包含项目文件
+    project: bool,
+    // 大小和修改时间；目标目录之外只用到修改时间，用于统计项目的活跃时间
+    stats: DirStats,
+    // 链接数大于 1 的文件：(dev, inode) 和链接数
+    link: Option<(InodeKey, u64)>,
+}
+
+impl std::fmt::Debug for EntryState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EntryState")
+            .field("detector", &self.detector.as_ref().map(|d| d.name()))
+            .field("relocated", &self.relocated.len())
+            .field("symlink", &self.symlink)
+            .field("project", &self.project)
+            .field("stats", &self.stats)
+            .field("link", &self.link)
+            .finish()
+    }
+}
+
+// 目录读取状态为 true 表示正在读取的目录位于目标目录内，只统计大小不再匹配
+type WalkState = (bool, EntryState);
+
+// 扫描引擎：一次并行遍历同时完成目标发现和大小统计。
+// 读取目录、匹配检测器和 stat 都在线程池中进行，结果按深度优先顺序流式产出，
+// 目标目录之外只保留子目录，内存占用只与单个目录的条目数有关
+fn walk(root: &Path, ctx: &Arc<ScanContext>, inside: bool) -> DirEntryIter<WalkState> {
+    let walk_ctx = Arc::clone(ctx);
+    WalkDirGeneric::<WalkState>::new(root)
+        .skip_hidden(false)
+        .follow_links(ctx.options.follow_symlinks)
+        .sort(true)
+        .parallelism(Parallelism::RayonExistingPool {
+            pool: Arc::clone(&ctx.pool),
+            busy_timeout: None,
+        })
+        .root_read_dir_state(inside)
+        .process_read_dir(move |depth, dir, inside, children| {
+            process_read_dir(&walk_ctx, depth, dir, inside, children)
+        })
+        .into_iter()
+}
+
+fn process_read_dir(
+    ctx: &ScanContext,
+    depth: Option<usize>,
+    dir: &Path,
+    inside: &mut bool,
+    children: &mut Vec<jwalk::Result<jwalk::DirEntry<WalkState>>>,
+) {
+    // depth 为 None 时 children 只有遍历根目录本身
+    if depth.is_some() && !*inside {
+        *inside = ctx
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(dir);
+    }
+    if *inside {
+        // 跟随符号链接时失效的链接和循环会产生错误项，它们不影响目标目录的大小
+        children.retain(|result| result.is_ok());
+        for entry in children.iter_mut().flatten() {
+            // 目标目录内的符号链接只统计链接本身，删除目标时也只会删除链接
+            if entry.path_is_symlink() {
+                entry.read_children_path = None;
+            }
+            // 挂载在目标目录内的其他文件系统不计入大小，也不再读取
+            if entry.read_children_path.is_some() && ctx.crosses_mount(&entry.path()) {
+                entry.read_children_path = None;
+                continue;
+            }
+            stat_entry(entry);
+        }
+        return;
+    }
+    if depth.is_none() {
+        for entry in children.iter_mut().flatten() {
+            let path = entry.path();
+            entry.client_state.relocated = ctx.relocated_dirs(&path);
+            entry.client_state.project = is_project(&path);
+            stat_entry(entry);
+        }
+        return;
+    }
+    children.retain_mut(|result| {
+        let Ok(entry) = result else {
+            return true;
+        };
+        let name = entry.file_name.to_string_lossy().to_string();
+        let path = entry.path();
+        // 没有跟随的符号链接：指向目录时仍可作为目标（只删除链接本身），但不会进入
+        let is_dir = if entry.file_type.is_symlink() {
+            fs::metadata(&path).is_ok_and(|m| m.is_dir())
+        } else {
+            entry.file_type.is_dir()
+        };
+        // 目标目录之外的文件只记录修改时间，用于统计所属项目的活跃时间
+        if !is_dir {
+            stat_entry(entry);
+            return true;
+        }
+        if ctx.skip_hidden(&name) || ctx.is_excluded(&path) {
+            return false;
+        }
+        if entry.path_is_symlink() && entry.read_children_path.is_some() && !ctx.follow_link(&path)
+        {
+            entry.read_children_path = None;
+        }
+        if entry.read_children_path.is_some() && ctx.crosses_mount(&path) {
+            return false;
+        }
+        if let Some(detector) = ctx.match_dir(&path, &name) {
+            if entry.path_is_symlink() {
+                entry.read_children_path = None;
+                entry.client_state.symlink = true;
             }
-            // info!("子目录处理完成: {:?}", local_entries);
-            local_entries
+            entry.client_state.detector = Some(detector);
+            if entry.read_children_path.is_some() {
+                ctx.pending
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(path);
+            }
+        } else if entry.read_children_path.is_some() {
+            entry.client_state.relocated = ctx.relocated_dirs(&path);
+            entry.client_state.project = is_project(&path);
+        } else {
+            return false;
+        }
+        stat_entry(entry);
+        true
+    });
+}
+
+// 记录单个条目的大小和修改时间；目录的表观大小不计（删除子项会更新它的 mtime）
+fn stat_entry(entry: &mut jwalk::DirEntry<WalkState>) {
+    let Ok(metadata) = fs::symlink_metadata(entry.path()) else {
+        return;
+    };
+    entry.client_state.stats = DirStats {
+        apparent: if metadata.is_dir() { 0 } else { metadata.len() },
+        disk: allocated_size(&metadata),
+        latest: metadata.modified().ok(),
+    };
+    if !metadata.is_dir() {
+        entry.client_state.link = hardlink(&metadata);
+    }
+}
+
+// 硬链接的标识：(设备号, inode)
+type InodeKey = (u64, u64);
+
+#[cfg(unix)]
+fn hardlink(metadata: &fs::Metadata) -> Option<(InodeKey, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    (metadata.nlink() > 1).then(|| ((metadata.dev(), metadata.ino()), metadata.nlink()))
+}
+
+#[cfg(not(unix))]
+fn hardlink(_metadata: &fs::Metadata) -> Option<(InodeKey, u64)> {
+    None
+}
+
+// 一个目标目录的大小统计。链接数大于 1 的文件按 (dev, inode) 去重：
+// 整个扫描中只计入第一个遇到它的目标；只有全部链接都在本目标内时，删除本目标才会释放它
+#[derive(Default)]
+struct SizeTally {
+    // 计入本目标的大小，累加各目标即为总的可释放空间
+    stats: DirStats,
+    // 没有其他链接的文件
+    exclusive: DirStats,
+    // 本目标内的硬链接：inode -> (链接数, 在本目标内出现的次数, 大小)
+    links: HashMap<InodeKey, (u64, u64, DirStats)>,
+}
+
+impl SizeTally {
+    fn add(&mut self, state: &EntryState, seen: &mut HashSet<InodeKey>) {
+        let Some((key, nlink)) = state.link else {
+            self.stats = self.stats.merge(state.stats);
+            self.exclusive = self.exclusive.merge(state.stats);
+            return;
+        };
+        self.links.entry(key).or_insert((nlink, 0, state.stats)).1 += 1;
+        if seen.insert(key) {
+            self.stats = self.stats.merge(state.stats);
+        } else {
+            self.stats.latest = self.stats.latest.max(state.stats.latest);
+        }
+    }
+
+    // (只删除本目标即可释放的大小, 与其他位置共享、删除后不会释放的大小)
+    fn split(&self) -> (DirStats, DirStats) {
+        self.links.values().fold(
+            (self.exclusive, DirStats::default()),
+            |(exclusive, shared), &(nlink, count, stats)| {
+                if count >= nlink {
+                    (exclusive.merge(stats), shared)
+                } else {
+                    (exclusive, shared.merge(stats))
+                }
+            },
+        )
+    }
+}
+
+// 统计整个目录的大小，seen 为整个扫描中已经计入的硬链接
+fn dir_stats(path: &Path, ctx: &Arc<ScanContext>, seen: &mut HashSet<InodeKey>) -> SizeTally {
+    let mut tally = SizeTally::default();
+    for entry in walk(path, ctx, true).flatten() {
+        tally.add(&entry.client_state, seen);
+    }
+    tally
+}
+
+// 正在统计大小的目标目录
+struct OpenTarget {
+    path: PathBuf,
+    depth: usize,
+    detector: Arc<dyn Detector>,
+    symlink: bool,
+    tally: SizeTally,
+}
+
+// 目标目录之外正在遍历的目录。项目的活跃时间在同一次遍历中统计：
+// 目录遍历完成时，其子树（不含目标目录和隐藏目录）中最新的 mtime 才确定
+struct OpenDir {
+    path: PathBuf,
+    depth: usize,
+    // 隐藏目录（如 .git 之外的 .cache）不计入上级目录的活跃时间
+    hidden: bool,
+    // 直接包含项目文件
+    project: bool,
+    latest: Option<SystemTime>,
+    // 已产出以该目录为所属项目的条目，遍历完成时需要补充活跃时间
+    has_entries: bool,
+}
+
+impl OpenDir {
+    // 遍历完成：产出项目的活跃时间，最新 mtime 计入上级目录
+    fn close(self, parent: Option<&mut OpenDir>, on_event: &mut impl FnMut(ScanEvent)) {
+        if self.has_entries {
+            on_event(ScanEvent::Activity(ProjectActivity {
+                project_path: get_canonical_path(&self.path),
+                project_activity: self.latest,
+            }));
+        }
+        if let Some(parent) = parent.filter(|_| !self.hidden) {
+            parent.latest = parent.latest.max(self.latest);
+        }
+    }
+}
+
+// 关闭深度不小于 depth 的目录：深度优先顺序下它们的子树已经遍历完
+fn close_dirs(dirs: &mut Vec<OpenDir>, depth: usize, on_event: &mut impl FnMut(ScanEvent)) {
+    while dirs.last().is_some_and(|dir| dir.depth >= depth) {
+        let dir = dirs.pop().unwrap();
+        dir.close(dirs.last_mut(), on_event);
+    }
+}
+
+// 目标目录所属的项目：最近的包含项目文件的上级目录，找不到时使用父目录。
+// dirs 只包含扫描根目录及其下的目录，因此不会超出扫描根目录
+fn project_of(dirs: &mut [OpenDir]) -> Option<&mut OpenDir> {
+    let index = dirs
+        .iter()
+        .rposition(|dir| dir.project)
+        .or(dirs.len().checked_sub(1))?;
+    Some(&mut dirs[index])
+}
+
+// 扫描 path，每个目标目录统计完大小就回调 on_event，所属项目遍历完成后再回调其活跃时间；
+// on_progress 在进入每个顶层目录时回调 (当前路径, 已处理数, 顶层目录总数)
+fn scan_tree(
+    path: &Path,
+    ctx: &Arc<ScanContext>,
+    mut on_progress: impl FnMut(&Path, usize, usize),
+    mut on_event: impl FnMut(ScanEvent),
+) {
+    let total_items = fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+                .filter(|e| !ctx.skip_hidden(&e.file_name().to_string_lossy()))
+                .count()
         })
-        .collect();
+        .unwrap_or(0);
+    let mut processed_items = 0;
+    let mut open: Option<OpenTarget> = None;
+    let mut dirs: Vec<OpenDir> = Vec::new();
+    let mut seen = HashSet::new();
+    let finish =
+        |target: OpenTarget, dirs: &mut Vec<OpenDir>, on_event: &mut dyn FnMut(ScanEvent)| {
+            let project = match project_of(dirs) {
+                Some(project) => {
+                    project.has_entries = true;
+                    project.path.clone()
+                }
+                None => target.path.parent().unwrap_or(&target.path).to_path_buf(),
+            };
+            on_event(ScanEvent::Entry(make_entry(
+                &target.path,
+                target.detector.as_ref(),
+                &project,
+                ctx,
+                &target.tally,
+                target.symlink,
+            )));
+        };
 
-    // 收集所有结果到主entries
-    for result in results {
-        entries.extend(result);
+    for result in walk(path, ctx, false) {
+        let mut entry = match result {
+            Ok(entry) => entry,
+            Err(e) if e.loop_ancestor().is_some() => {
+                info!("跳过符号链接循环: {}", e);
+                continue;
+            }
+            Err(e) => {
+                eprintln!("ls: cannot access: {}", e);
+                continue;
+            }
+        };
+        if let Some(e) = &entry.read_children_error {
+            eprintln!("无法读取目录 {}: {}", entry.path().display(), e);
+        }
+        // 深度优先顺序下，回到目标目录的同级或上级说明它的子树已经遍历完
+        if open.as_ref().is_some_and(|t| entry.depth <= t.depth) {
+            finish(open.take().unwrap(), &mut dirs, &mut on_event);
+        }
+        if let Some(target) = open.as_mut() {
+            target.tally.add(&entry.client_state, &mut seen);
+            continue;
+        }
+        close_dirs(&mut dirs, entry.depth, &mut on_event);
+
+        // 目标目录之外的文件只计入所在目录的最新修改时间
+        if entry.read_children_path.is_none() && entry.client_state.detector.is_none() {
+            if let Some(dir) = dirs.last_mut() {
+                dir.latest = dir.latest.max(entry.client_state.stats.latest);
+            }
+            continue;
+        }
+        let entry_path = entry.path();
+        let state = &mut entry.client_state;
+        if entry.depth == 1 {
+            processed_items += 1;
+            on_progress(&entry_path, processed_items, total_items);
+        }
+        if let Some(detector) = state.detector.take() {
+            info!(
+                "命中目录: {:?}, detector: {:?}",
+                entry_path,
+                detector.name()
+            );
+            let mut tally = SizeTally::default();
+            tally.add(state, &mut seen);
+            open = Some(OpenTarget {
+                path: entry_path,
+                depth: entry.depth,
+                detector,
+                symlink: state.symlink,
+                tally,
+            });
+            continue;
+        }
+        let mut dir = OpenDir {
+            hidden: entry.depth > 0 && entry.file_name.to_string_lossy().starts_with('.'),
+            path: entry_path,
+            depth: entry.depth,
+            project: state.project,
+            latest: state.stats.latest,
+            has_entries: false,
+        };
+        // 配置到别处的构建目录属于当前目录这个项目
+        for (detector, relocated) in std::mem::take(&mut state.relocated) {
+            let tally = dir_stats(&relocated, ctx, &mut seen);
+            let entry = make_entry(&relocated, detector.as_ref(), &dir.path, ctx, &tally, false);
+            dir.has_entries = true;
+            on_event(ScanEvent::Entry(entry));
+        }
+        dirs.push(dir);
+    }
+    if let Some(target) = open.take() {
+        finish(target, &mut dirs, &mut on_event);
+    }
+    close_dirs(&mut dirs, 0, &mut on_event);
+}
+
+// 为命中的目录构建条目，project 为其所属项目；项目的活跃时间在项目遍历完成后单独产出
+fn make_entry(
+    path: &Path,
+    detector: &dyn Detector,
+    project: &Path,
+    ctx: &ScanContext,
+    tally: &SizeTally,
+    symlink: bool,
+) -> FileEntry {
+    let stats = tally.stats;
+    let (exclusive, shared) = tally.split();
+    let size = |stats: DirStats| match ctx.options.size_mode {
+        SizeMode::Apparent => stats.apparent,
+        SizeMode::Disk => stats.disk,
+    };
+    let raw = size(stats);
+    // 经跟随的符号链接找到的目录实际位于扫描根目录之外，删除前的安全检查会拒绝它
+    let report_only = canonicalize_parent(path).is_ok_and(|real| !real.starts_with(&ctx.root));
+    FileEntry {
+        file_type: if symlink { 'l' } else { 'd' },
+        permissions: "rwx".to_string(),
+        size_display: human_readable_size(raw),
+        size_raw: raw,
+        size_apparent: stats.apparent,
+        size_disk: stats.disk,
+        size_exclusive: size(exclusive),
+        size_shared: size(shared),
+        path: get_canonical_path(path),
+        matched_pattern: detector.name().to_string(),
+        risk: detector.risk(),
+        last_modified: stats.latest,
+        project_path: get_canonical_path(project),
+        project_activity: None,
+        report_only,
+        delete_status: NotDeleted,
     }
 }
 
 fn get_canonical_path(path: &Path) -> String {
-    match path.canonicalize() {
+    match canonicalize_parent(path) {
         Ok(canonical) => {
             let s = canonical.to_string_lossy().into_owned();
             s.strip_prefix(r"\\?\").unwrap_or(&s).to_string()
@@ -375,8 +869,9 @@ fn get_canonical_path(path: &Path) -> String {
 // 添加新的扫描函数，支持进度更新
 pub fn scan_directory_with_progress(
     path: &Path,
+    options: &ScanOptions,
     status_tx: &Sender<ScanStatus>,
-    entries_tx: &Sender<FileEntry>, // 添加这个参数
+    entries_tx: &Sender<ScanEvent>, // 添加这个参数
 ) {
     // 发送初始状态
     let _ = status_tx.send(ScanStatus::Scanning {
@@ -386,173 +881,266 @@ pub fn scan_directory_with_progress(
         processed_items: 0,
     });
 
-    let entries = match fs::read_dir(path) {
-        Ok(entries) => entries,
-        Err(e) => {
-            eprintln!("ls: cannot access '{}': {}", path.display(), e);
-            return;
-        }
-    };
+    let ctx = ScanContext::new(options, path);
+    let mut total_files = 0;
+    let mut total_size = 0;
+    scan_tree(
+        path,
+        &ctx,
+        |current, processed_items, total_items| {
+            let progress = (processed_items as f64 / total_items.max(1) as f64 * 100.0) as u16;
+            let _ = status_tx.send(ScanStatus::Scanning {
+                current_path: current.display().to_string(),
+                progress: progress.min(100),
+                total_items,
+                processed_items,
+            });
+        },
+        |event| {
+            if let ScanEvent::Entry(entry) = &event {
+                total_files += 1;
+                total_size += entry.size_raw;
+            }
+            let _ = entries_tx.send(event);
+        },
+    );
 
-    let mut files: Vec<String> = Vec::new();
+    // 发送完成状态
+    let _ = status_tx.send(ScanStatus::Completed {
+        total_files,
+        total_size: human_readable_size(total_size),
+        excluded: ctx.excluded.load(Ordering::Relaxed),
+        skipped_mounts: ctx
+            .skipped_mounts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect(),
+    });
+}
 
-    for entry in entries.flatten() {
-        let file_name = entry.file_name().to_string_lossy().to_string();
-        files.push(file_name);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exclude_matches_names_and_paths() {
+        let patterns = vec!["vendor".to_string(), "/data/**/sdk".to_string()];
+        let exclude = build_exclude(&patterns, Path::new("/")).unwrap();
+        assert!(exclude.is_match("/home/u/app/vendor"));
+        assert!(!exclude.is_match("/home/u/app/vendored"));
+        assert!(exclude.is_match("/data/android/sdk"));
+        assert!(!exclude.is_match("/home/u/sdk"));
     }
 
-    files.sort();
-    let total_files = files.len();
-    let mut processed_files = 0;
-    let mut entries = Vec::new();
-    let name = String::from("node_modules");
+    #[test]
+    fn relative_exclude_is_anchored_to_root() {
+        let patterns = vec!["third_party/sdk".to_string(), "./out/".to_string()];
+        let exclude = build_exclude(&patterns, Path::new("/nonexistent/repo")).unwrap();
+        assert!(exclude.is_match("/nonexistent/repo/third_party/sdk"));
+        assert!(!exclude.is_match("/nonexistent/repo/app/third_party/sdk"));
+        assert!(exclude.is_match("/nonexistent/repo/out"));
+        assert!(!exclude.is_match("/nonexistent/repo/app/out"));
+    }
 
-    for (_i, file) in files.iter().enumerate() {
-        let file_path = path.join(&file);
+    #[cfg(unix)]
+    #[test]
+    fn scan_reports_each_target_once() {
+        use crate::models::Preset;
 
-        // 更新进度
-        processed_files += 1;
-        let progress = (processed_files as f64 / total_files as f64 * 100.0) as u16;
-        let _ = status_tx.send(ScanStatus::Scanning {
-            current_path: file_path.display().to_string(),
-            progress,
-            total_items: total_files,
-            processed_items: 0,
-        });
+        let root = std::env::temp_dir().join(format!("rust_kill-scan-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let write = |path: &str, len: usize| {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, vec![0u8; len]).unwrap();
+        };
+        // 嵌套的 node_modules 计入外层目标，不单独报告
+        write("app/package.json", 0);
+        write("app/node_modules/a.js", 1000);
+        write("app/node_modules/pkg/node_modules/b.js", 500);
+        write(".hidden/node_modules/c.js", 100);
+        write("vendor/lib/node_modules/d.js", 100);
+        // 两个成员共享同一个配置到别处的构建目录，只报告一次
+        write("ws/a/Cargo.toml", 0);
+        write("ws/b/Cargo.toml", 0);
+        fs::create_dir_all(root.join("ws/.cargo")).unwrap();
+        fs::write(
+            root.join("ws/.cargo/config.toml"),
+            "[build]\ntarget-dir = \"out\"\n",
+        )
+        .unwrap();
+        write("ws/out/debug/app", 200);
+        fs::create_dir_all(root.join("linked")).unwrap();
+        std::os::unix::fs::symlink(
+            root.join("app/node_modules"),
+            root.join("linked/node_modules"),
+        )
+        .unwrap();
+        let root = root.canonicalize().unwrap();
 
-        let metadata = match file_path.metadata() {
-            Ok(m) => m,
-            Err(e) => {
-                eprintln!("ls: cannot access '{}': {}", file_path.display(), e);
-                continue;
-            }
+        let scan = |hidden: bool| {
+            let options = ScanOptions {
+                detectors: Registry::new(&[Preset::Node, Preset::Rust], &[]),
+                gitignored_only: false,
+                exclude: build_exclude(&["vendor".to_string()], &root).unwrap(),
+                hidden,
+                size_mode: SizeMode::Apparent,
+                one_file_system: false,
+                follow_symlinks: false,
+            };
+            let mut found: Vec<(String, char, u64)> = scan_directory(&root, &options)
+                .into_iter()
+                .map(|e| {
+                    let path = Path::new(&e.path).strip_prefix(&root).unwrap();
+                    (path.display().to_string(), e.file_type, e.size_apparent)
+                })
+                .collect();
+            found.sort();
+            found
         };
 
-        if metadata.is_dir() {
-            // 如果是目录，是否跟要搜索的名称匹配
-            if !file.contains(&name) {
-                calculate_dir_size_parallel_v2(
-                    file_path,
-                    true,
-                    &name,
-                    status_tx,
-                    entries_tx,
-                    &mut entries,
-                );
-                continue;
-            }
-        } else {
-            continue;
+        let found = scan(true);
+        let link_size = fs::symlink_metadata(root.join("linked/node_modules"))
+            .unwrap()
+            .len();
+        let mut expected = vec![
+            (".hidden/node_modules".to_string(), 'd', 100),
+            ("app/node_modules".to_string(), 'd', 1500),
+            ("linked/node_modules".to_string(), 'l', link_size),
+        ];
+        // CARGO_TARGET_DIR 优先于配置文件，设置了它时配置的构建目录不会生效
+        if std::env::var_os("CARGO_TARGET_DIR").is_none() {
+            expected.push(("ws/out".to_string(), 'd', 200));
         }
-    }
+        expected.sort();
+        assert_eq!(found, expected);
 
-    // 计算总大小
-    let total_size: u64 = entries.iter().map(|e| e.size_raw).sum();
+        let found = scan(false);
+        assert!(found
+            .iter()
+            .all(|(path, _, _)| !path.starts_with(".hidden")));
+        assert!(found.iter().any(|(path, _, _)| path == "app/node_modules"));
 
-    // 发送完成状态
-    let _ = status_tx.send(ScanStatus::Completed {
-        total_files: entries.len(),
-        total_size: human_readable_size(total_size),
-    });
+        fs::remove_dir_all(&root).unwrap();
+    }
 
-    // entries
-}
+    #[cfg(unix)]
+    #[test]
+    fn followed_link_targets_are_report_only() {
+        use crate::models::Preset;
+        use crate::protect::Protection;
 
-// 搜索文件
-fn calculate_dir_size_parallel_v2(
-    file_path: PathBuf,
-    human_readable: bool,
-    name: &str,
-    status_tx: &Sender<ScanStatus>,
-    entries_tx: &Sender<FileEntry>,
-    entries: &mut Vec<FileEntry>,
-) {
-    let sub_entries = match fs::read_dir(&file_path) {
-        Ok(entries) => entries,
-        Err(e) => {
-            eprintln!("ls: cannot access '{}': {}", file_path.display(), e);
-            return;
+        let tmp = std::env::temp_dir().join(format!("rust_kill-follow-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+        for dir in ["root/app/node_modules", "ext/proj/node_modules"] {
+            fs::create_dir_all(tmp.join(dir)).unwrap();
         }
-    };
+        fs::write(tmp.join("root/app/package.json"), "{}").unwrap();
+        fs::write(tmp.join("ext/proj/package.json"), "{}").unwrap();
+        std::os::unix::fs::symlink(tmp.join("ext"), tmp.join("root/link")).unwrap();
+        let tmp = tmp.canonicalize().unwrap();
+        let root = tmp.join("root");
 
-    // 收集所有需要处理的目录
-    let dirs_to_process: Vec<_> = sub_entries
-        .filter_map(|e| e.ok())
-        .filter_map(|e| {
-            let name = e.file_name().to_string_lossy().to_string();
-            if name.starts_with('.') {
-                return None;
-            }
-            let metadata = e.metadata().ok()?;
-            if !metadata.is_dir() {
-                return None;
-            }
-            Some((e.path(), name))
-        })
-        .collect();
-
-    let total_items = dirs_to_process.len();
-    // 使用原子类型来跟踪已处理的项数
-    let processed_items = std::sync::atomic::AtomicUsize::new(0);
-
-    // 并行处理每个子目录
-    let results: Vec<Vec<FileEntry>> = dirs_to_process
-        .into_par_iter()
-        .enumerate()
-        .map(|(i, (sub_path, sub_name))| {
-            let mut local_entries = Vec::new();
-            if sub_name.contains(name) {
-                // 匹配：计算大小
-                let (raw, converted) =
-                    calculate_dir_size(&sub_path, human_readable, &ProgressBar::hidden(), true);
-                let entry = FileEntry {
-                    file_type: 'd',
-                    permissions: "rwx".to_string(),
-                    size_display: converted,
-                    size_raw: raw,
-                    path: get_canonical_path(&sub_path),
-                    delete_status: NotDeleted,
-                };
-                info!(
-                    "子目录: {:?},name:{:?},local_entries:{:?}",
-                    sub_name, name, local_entries
-                );
-
-                // 发送条目到通道
-                let _ = entries_tx.send(entry.clone());
-
-                // 原子地更新已处理项数
-                let processed =
-                    processed_items.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
-                let progress = if total_items > 0 {
-                    (processed as f64 / total_items as f64 * 100.0) as u16
-                } else {
-                    0
-                };
-                let _ = status_tx.send(ScanStatus::Scanning {
-                    current_path: sub_path.display().to_string(),
-                    progress,
-                    total_items,
-                    processed_items: processed,
-                });
-
-                local_entries.push(entry);
-            } else {
-                calculate_dir_size_parallel_v2(
-                    sub_path,
-                    human_readable,
-                    name,
-                    status_tx,
-                    entries_tx,
-                    &mut local_entries,
-                );
+        let options = ScanOptions {
+            detectors: Registry::new(&[Preset::Node], &[]),
+            gitignored_only: false,
+            exclude: build_exclude(&[], &root).unwrap(),
+            hidden: false,
+            size_mode: SizeMode::Apparent,
+            one_file_system: false,
+            follow_symlinks: true,
+        };
+        let mut found: Vec<(String, bool)> = scan_directory(&root, &options)
+            .into_iter()
+            .map(|e| (e.path, e.report_only))
+            .collect();
+        found.sort();
+        let inside = root.join("app/node_modules").display().to_string();
+        let outside = tmp.join("ext/proj/node_modules").display().to_string();
+        assert_eq!(
+            found,
+            vec![(outside.clone(), true), (inside.clone(), false)]
+        );
+
+        // 只报告的条目正是删除前的安全检查会拒绝的路径
+        let protection = Protection::new(&root, &[]).unwrap();
+        assert!(protection.check(Path::new(&inside)).is_ok());
+        assert!(protection.check(Path::new(&outside)).is_err());
+
+        fs::remove_dir_all(&tmp).unwrap();
+    }
+
+    #[test]
+    fn entries_are_emitted_before_project_activity() {
+        use crate::models::Preset;
+
+        let root = std::env::temp_dir().join(format!("rust_kill-events-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("a/node_modules")).unwrap();
+        fs::create_dir_all(root.join("z")).unwrap();
+        fs::write(root.join("package.json"), "{}").unwrap();
+        fs::write(root.join("z/late.txt"), "").unwrap();
+        let root = root.canonicalize().unwrap();
+
+        let options = ScanOptions {
+            detectors: Registry::new(&[Preset::Node], &[]),
+            gitignored_only: false,
+            exclude: build_exclude(&[], &root).unwrap(),
+            hidden: false,
+            size_mode: SizeMode::Apparent,
+            one_file_system: false,
+            follow_symlinks: false,
+        };
+        let ctx = ScanContext::new(&options, &root);
+        let mut events = Vec::new();
+        scan_tree(&root, &ctx, |_, _, _| {}, |event| events.push(event));
+
+        // 根目录是项目：条目不等整个根目录遍历完就产出，活跃时间最后单独产出
+        let latest = fs::metadata(root.join("z/late.txt"))
+            .unwrap()
+            .modified()
+            .ok();
+        match events.as_slice() {
+            [ScanEvent::Entry(entry), ScanEvent::Activity(project)] => {
+                assert_eq!(entry.project_activity, None);
+                assert_eq!(project.project_path, entry.project_path);
+                assert!(project.project_activity >= latest);
             }
-            local_entries
-        })
-        .collect();
+            other => panic!("unexpected events: {:?}", other),
+        }
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn hardlinks_are_counted_once() {
+        let file = |apparent, link| EntryState {
+            stats: DirStats {
+                apparent,
+                disk: apparent,
+                latest: None,
+            },
+            link,
+            ..Default::default()
+        };
+        let mut seen = HashSet::new();
+        // a 中有一个独立文件，以及另一个目录也链接着的 inode 1
+        let mut a = SizeTally::default();
+        a.add(&file(300, None), &mut seen);
+        a.add(&file(1000, Some(((1, 1), 2))), &mut seen);
+        // b 中的两个链接都指向 inode 2，删除 b 即可释放
+        let mut b = SizeTally::default();
+        b.add(&file(1000, Some(((1, 1), 2))), &mut seen);
+        b.add(&file(500, Some(((1, 2), 2))), &mut seen);
+        b.add(&file(500, Some(((1, 2), 2))), &mut seen);
 
-    // 收集所有结果到主entries
-    for result in results {
-        entries.extend(result);
+        assert_eq!(a.stats.apparent, 1300);
+        assert_eq!(b.stats.apparent, 500);
+        let (exclusive, shared) = a.split();
+        assert_eq!((exclusive.apparent, shared.apparent), (300, 1000));
+        let (exclusive, shared) = b.split();
+        assert_eq!((exclusive.apparent, shared.apparent), (500, 1000));
     }
 }
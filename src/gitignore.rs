@@ -0,0 +1,90 @@
+// 只清理 git 忽略的目录：候选目录必须位于 git 工作区内，并且被该仓库的忽略规则
+// （各级 .gitignore、.git/info/exclude、全局 core.excludesFile）忽略，
+// 避免误删恰好叫 build / target 但已提交到仓库的目录
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub fn is_ignored(path: &Path) -> bool {
+    let Ok(path) = path.canonicalize() else {
+        return false;
+    };
+    let Some(root) = path
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.join(".git").exists())
+    else {
+        return false;
+    };
+
+    // 按优先级从高到低：离目标最近的 .gitignore ... 仓库根目录的 .gitignore，
+    // 然后是 info/exclude 和全局规则；第一个给出结论的规则生效
+    let mut matchers: Vec<Gitignore> = path
+        .ancestors()
+        .skip(1)
+        .take_while(|dir| dir.starts_with(root))
+        .map(|dir| matcher(dir, &dir.join(".gitignore")))
+        .collect();
+    if let Some(git_dir) = git_dir(root) {
+        matchers.push(matcher(root, &git_dir.join("info").join("exclude")));
+    }
+    matchers.push(GitignoreBuilder::new(root).build_global().0);
+
+    for gitignore in &matchers {
+        match gitignore.matched_path_or_any_parents(&path, true) {
+            Match::Ignore(_) => return true,
+            Match::Whitelist(_) => return false,
+            Match::None => {}
+        }
+    }
+    false
+}
+
+fn matcher(root: &Path, file: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(root);
+    if file.is_file() {
+        let _ = builder.add(file);
+    }
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
+
+// .git 可能是目录，也可能是指向真实 git 目录的文件（worktree、submodule）
+fn git_dir(root: &Path) -> Option<PathBuf> {
+    let dot_git = root.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+    let text = fs::read_to_string(&dot_git).ok()?;
+    let git_dir = root.join(text.strip_prefix("gitdir:")?.trim());
+    // worktree 的 info/exclude 在主仓库的 git 目录中
+    match fs::read_to_string(git_dir.join("commondir")) {
+        Ok(common) => Some(git_dir.join(common.trim())),
+        Err(_) => Some(git_dir),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_ignored_dirs_inside_a_repo() {
+        let repo = std::env::temp_dir().join(format!("rust_kill-gitignore-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&repo);
+        fs::create_dir_all(repo.join(".git/info")).unwrap();
+        fs::create_dir_all(repo.join("app/target")).unwrap();
+        fs::create_dir_all(repo.join("app/build")).unwrap();
+        fs::create_dir_all(repo.join("vendor/build")).unwrap();
+        fs::write(repo.join(".gitignore"), "target/\n").unwrap();
+        fs::write(repo.join(".git/info/exclude"), "build/\n").unwrap();
+        fs::write(repo.join("vendor/.gitignore"), "!build/\n").unwrap();
+
+        assert!(is_ignored(&repo.join("app/target")));
+        assert!(is_ignored(&repo.join("app/build")));
+        assert!(!is_ignored(&repo.join("vendor/build")));
+        assert!(!is_ignored(&repo.join("vendor")));
+
+        fs::remove_dir_all(&repo).unwrap();
+    }
+}
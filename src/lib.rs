@@ -1,5 +1,6 @@
+pub mod detector;
 // pub mod dir_listing;
 // pub mod logger;
-// pub mod models;
-// pub mod utils;
+pub mod models;
+pub mod utils;
 // pub use dir_listing::list_directory;
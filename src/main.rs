@@ -1,26 +1,46 @@
+pub mod config;
+pub mod delete;
 pub mod dir_listing;
+pub mod gitignore;
 pub mod logger;
-pub mod models;
-pub mod utils;
-pub use dir_listing::{list_directory, scan_directory_with_progress};
+pub mod output;
+pub mod plan;
+pub mod protect;
+pub mod quarantine;
+pub mod trash;
+// 检测器、数据模型和工具函数在库中，供其他工具复用
+pub use dir_listing::{
+    build_exclude, list_directory, print_entries_table, scan_directory, scan_directory_streaming,
+    scan_directory_with_progress, ScanEvent, ScanOptions,
+};
+use rust_kill::{detector, models, utils};
 
 use clap::Parser;
 use logger::init_logger;
+use std::collections::{BTreeSet, HashMap};
+use std::io::{self, IsTerminal};
 use std::path::Path;
 use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
 use std::time::{Duration, Instant};
-use std::{fs, thread};
 
+use comfy_table::{ContentArrangement, Table};
 use crossterm::event::{self, KeyCode, KeyEventKind};
-use models::Cli;
-use ratatui::layout::{Alignment, Constraint, Direction, Layout};
+use models::{format_time, Cli, Command, OutputFormat};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span, Text};
-use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap};
 use ratatui::Frame;
 
-use crate::models::{DeleteStatus, FileEntry};
-use crate::utils::human_readable_size;
+use crate::config::Config;
+use crate::delete::{DeleteBackend, DeletePool, DeleteResult};
+use crate::detector::{Registry, RiskLevel};
+use crate::models::{DeleteStatus, FileEntry, SizeMode};
+use crate::plan::DeletionPlan;
+use crate::protect::Protection;
+use crate::utils::{format_age, fuzzy_match, human_readable_size, truncate_start};
 
 fn main() -> Result<(), anyhow::Error> {
     init_logger();
@@ -29,11 +49,102 @@ fn main() -> Result<(), anyhow::Error> {
     let args = Cli::parse();
     let path = Path::new(&args.dir);
 
+    if let Some(Command::Restore { target }) = &args.command {
+        return run_restore(target.as_deref());
+    }
+
     // 检查是否启用了交互式搜索模式
     if path.is_dir() {
+        let mut detectors = Registry::new(&args.presets, &args.dirs_to_delete);
+        if args.cachedir_tag {
+            detectors.enable_cachedir_tag();
+        }
+        let config = Config::load(args.config.as_deref())?;
+        let exclude: Vec<String> = config
+            .exclude
+            .iter()
+            .chain(&args.exclude)
+            .cloned()
+            .collect();
+        let options = ScanOptions {
+            detectors,
+            gitignored_only: args.gitignored_only,
+            exclude: build_exclude(&exclude, path)?,
+            hidden: args.hidden,
+            size_mode: args.size_mode,
+            one_file_system: args.one_file_system,
+            follow_symlinks: args.follow_symlinks,
+        };
+        // 干运行：在终端中启动只能标记、不能删除的 TUI；--force、--format 或输出被重定向时
+        // 完整扫描后打印删除计划。两种方式都不做任何删除
+        if args.dry_run && (args.force || args.format.is_some() || !io::stdout().is_terminal()) {
+            let mut entries = scan_directory(path, &options);
+            entries.retain(keep_stale(args.older_than));
+            skip_report_only(&mut entries);
+            let plan = DeletionPlan::new(entries);
+            match args.format {
+                Some(format) => plan.write(format)?,
+                None => plan.print(),
+            }
+            return Ok(());
+        }
+        // 删除后端：默认直接删除，--trash 时移入回收站，--quarantine 时移入隔离区
+        let backend = if args.trash {
+            DeleteBackend::Trash
+        } else if args.quarantine {
+            DeleteBackend::Quarantine
+        } else {
+            DeleteBackend::Remove
+        };
+        // 删除前的安全检查：内置拒绝列表 + 配置文件和 --protect 中的受保护路径
+        let protect: Vec<String> = config
+            .protect
+            .iter()
+            .chain(&args.protect)
+            .cloned()
+            .collect();
+        let protection = Arc::new(Protection::new(path, &protect)?);
+        // 只在会删除的模式下（--force，或以 --quarantine 启动 TUI）清除过期的隔离项，
+        // 干运行、只输出报告和 restore 都不会删除任何东西
+        if !args.dry_run && (args.force || (args.quarantine && args.format.is_none())) {
+            purge_expired_quarantine(args.retention);
+        }
+        // 强制模式：无界面扫描并删除全部匹配目录
+        if args.force {
+            return run_batch_cleanup(
+                path,
+                &options,
+                args.format,
+                backend,
+                &protection,
+                args.older_than,
+            );
+        }
+        // 机器可读输出：只扫描不删除
+        if let Some(format) = args.format {
+            return run_report(path, &options, format, args.older_than);
+        }
         // 使用TUI显示结果
-        match scan_directory_with_ui(path) {
-            Ok(_) => {}
+        match scan_directory_with_ui(
+            path,
+            &options,
+            backend,
+            protection,
+            args.older_than,
+            args.confirm(),
+            args.dry_run,
+        ) {
+            Ok(entries) => {
+                print_session_summary(&entries);
+                // TUI 中干运行标记的条目，退出后打印删除计划
+                let marked: Vec<FileEntry> = entries
+                    .into_iter()
+                    .filter(|e| matches!(e.delete_status, DeleteStatus::Marked))
+                    .collect();
+                if !marked.is_empty() {
+                    DeletionPlan::new(marked).print();
+                }
+            }
             Err(e) => {
                 eprintln!("扫描失败: {}", e);
             }
@@ -43,6 +154,206 @@ fn main() -> Result<(), anyhow::Error> {
     }
     Ok(())
 }
+// 清除超过保留期的隔离项
+fn purge_expired_quarantine(retention: Duration) {
+    match quarantine::purge_expired(retention) {
+        Ok(purged) => {
+            for record in purged {
+                log::info!("隔离项已过期并清除: {:?}", record.original_path);
+            }
+        }
+        Err(e) => log::info!("清除过期隔离项失败: {}", e),
+    }
+}
+
+// 批量清理：扫描、并行删除，然后打印汇总表
+fn run_batch_cleanup(
+    path: &Path,
+    options: &ScanOptions,
+    format: Option<OutputFormat>,
+    backend: DeleteBackend,
+    protection: &Protection,
+    older_than: Option<Duration>,
+) -> Result<(), anyhow::Error> {
+    let mut entries = scan_directory(path, options);
+    entries.retain(keep_stale(older_than));
+    skip_report_only(&mut entries);
+    if entries.is_empty() && format.is_none() {
+        println!("没有找到需要删除的目录");
+        return Ok(());
+    }
+
+    let results = delete::delete_entries(entries, backend, protection);
+    match format {
+        Some(format) => output::write_entries(&mut io::stdout().lock(), format, &results)?,
+        None => print_entries_table(&results),
+    }
+
+    let freed: u64 = results
+        .iter()
+        .filter(|e| matches!(e.delete_status, DeleteStatus::Deleted))
+        .map(|e| e.size_exclusive)
+        .sum();
+    let failed = results
+        .iter()
+        .filter(|e| matches!(e.delete_status, DeleteStatus::Failed(_)))
+        .count();
+    let summary = match backend {
+        DeleteBackend::Remove => format!(
+            "已删除 {} 个目录，释放 {}",
+            results.len() - failed,
+            human_readable_size(freed)
+        ),
+        DeleteBackend::Trash => format!("已将 {} 个目录移入回收站", results.len() - failed),
+        DeleteBackend::Quarantine => format!(
+            "已将 {} 个目录移入隔离区，可用 `rust_kill restore <id>` 恢复",
+            results.len() - failed
+        ),
+    };
+    // 有机器可读输出时汇总写到 stderr，避免污染 stdout
+    if format.is_some() {
+        eprintln!("{}", summary);
+    } else {
+        println!("{}", summary);
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{} 个目录删除失败", failed);
+    }
+    Ok(())
+}
+
+// 从隔离区恢复；没有指定目标时列出隔离区内容
+fn run_restore(target: Option<&str>) -> Result<(), anyhow::Error> {
+    let Some(target) = target else {
+        let records = quarantine::list()?;
+        if records.is_empty() {
+            println!("隔离区为空");
+            return Ok(());
+        }
+        let mut table = Table::new();
+        table
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(vec!["ID", "隔离时间", "原路径"])
+            .load_preset(comfy_table::presets::UTF8_FULL)
+            .apply_modifier(comfy_table::modifiers::UTF8_ROUND_CORNERS);
+        for record in &records {
+            table.add_row(vec![
+                record.id.clone(),
+                format_time(record.quarantined_time()),
+                record.original_path.display().to_string(),
+            ]);
+        }
+        println!("{}", table);
+        return Ok(());
+    };
+
+    let record = quarantine::restore(target)?;
+    println!("已恢复 {} -> {}", record.id, record.original_path.display());
+    Ok(())
+}
+
+// 扫描并以机器可读格式输出结果；NDJSON 边扫描边输出
+fn run_report(
+    path: &Path,
+    options: &ScanOptions,
+    format: OutputFormat,
+    older_than: Option<Duration>,
+) -> Result<(), anyhow::Error> {
+    let keep = keep_stale(older_than);
+    let mut stdout = io::stdout().lock();
+    match format {
+        OutputFormat::Ndjson => {
+            // 条目统计完大小就输出，项目遍历完成后再输出一行它的活跃时间；
+            // 指定 --older-than 时需要活跃时间才能过滤，条目等到所属项目遍历完成再输出
+            let mut waiting: HashMap<String, Vec<FileEntry>> = HashMap::new();
+            let mut result = Ok(());
+            scan_directory_streaming(path, options, |event| {
+                if result.is_err() {
+                    return;
+                }
+                result = match event {
+                    ScanEvent::Entry(entry) if older_than.is_some() => {
+                        waiting
+                            .entry(entry.project_path.clone())
+                            .or_default()
+                            .push(entry);
+                        Ok(())
+                    }
+                    ScanEvent::Entry(entry) => output::write_ndjson_line(&mut stdout, &entry),
+                    ScanEvent::Activity(project) if older_than.is_some() => waiting
+                        .remove(&project.project_path)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|mut entry| {
+                            entry.set_activity(project.project_activity);
+                            entry
+                        })
+                        .filter(&keep)
+                        .try_for_each(|entry| output::write_ndjson_line(&mut stdout, &entry)),
+                    ScanEvent::Activity(project) => {
+                        output::write_ndjson_line(&mut stdout, &project)
+                    }
+                };
+            });
+            result?;
+        }
+        _ => {
+            let mut entries = scan_directory(path, options);
+            entries.retain(keep);
+            output::write_entries(&mut stdout, format, &entries)?;
+        }
+    }
+    Ok(())
+}
+
+// TUI 退出后打印本次会话实际释放的空间
+fn print_session_summary(entries: &[FileEntry]) {
+    let deleted: Vec<&FileEntry> = entries
+        .iter()
+        .filter(|e| matches!(e.delete_status, DeleteStatus::Deleted))
+        .collect();
+    let freed: u64 = deleted.iter().map(|e| e.size_exclusive).sum();
+    println!(
+        "本次共删除 {} 个目录，释放 {}",
+        deleted.len(),
+        human_readable_size(freed)
+    );
+    let moved = entries
+        .iter()
+        .filter(|e| {
+            matches!(
+                e.delete_status,
+                DeleteStatus::Trashed | DeleteStatus::Quarantined(_)
+            )
+        })
+        .count();
+    if moved > 0 {
+        println!("另有 {} 个目录移入回收站/隔离区，清空后才会释放空间", moved);
+    }
+}
+
+// 按 --older-than 过滤：只保留所在项目长时间未活跃的条目
+fn keep_stale(older_than: Option<Duration>) -> impl Fn(&FileEntry) -> bool {
+    move |entry| older_than.is_none_or(|d| entry.is_stale(d))
+}
+
+// 批量删除和删除计划不包含只报告的条目，跳过时在 stderr 提示
+fn skip_report_only(entries: &mut Vec<FileEntry>) {
+    let before = entries.len();
+    entries.retain(|e| !e.report_only);
+    let skipped = before - entries.len();
+    if skipped > 0 {
+        eprintln!(
+            "跳过 {} 个经符号链接找到、位于扫描根目录之外的目录（只报告，不删除）",
+            skipped
+        );
+    }
+}
+
+// TUI 中按 o 切换过滤时，未指定 --older-than 使用的默认阈值
+const DEFAULT_OLDER_THAN: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
 // 定义扫描状态
 #[derive(Debug, Clone)]
 pub enum ScanStatus {
@@ -57,109 +368,397 @@ pub enum ScanStatus {
     Completed {
         total_files: usize,
         total_size: String,
+        // 被 --exclude / 配置文件排除规则跳过的目录数
+        excluded: usize,
+        // --one-file-system 时跳过的其他文件系统的挂载点
+        skipped_mounts: Vec<String>,
     },
 }
 // 扫描目录并显示进度
-fn scan_directory_with_ui(path: &Path) -> color_eyre::Result<Vec<FileEntry>> {
+fn scan_directory_with_ui(
+    path: &Path,
+    options: &ScanOptions,
+    backend: DeleteBackend,
+    protection: Arc<Protection>,
+    older_than: Option<Duration>,
+    confirm: bool,
+    dry_run: bool,
+) -> color_eyre::Result<Vec<FileEntry>> {
     let (status_tx, status_rx) = mpsc::channel::<ScanStatus>();
-    let (result_tx, result_rx) = mpsc::channel::<FileEntry>();
+    let (result_tx, result_rx) = mpsc::channel::<ScanEvent>();
 
     // 在后台线程中执行扫描
     let path_clone = path.to_path_buf();
+    let scan_options = options.clone();
     thread::spawn(move || {
         // 调用实际的扫描函数
-        scan_directory_with_progress(&path_clone, &status_tx, &result_tx);
+        scan_directory_with_progress(&path_clone, &scan_options, &status_tx, &result_tx);
     });
 
     // 运行TUI界面显示扫描进度
-    let entries = run_scan_ui(status_rx, result_rx)?;
+    let app = ScanApp::new(
+        older_than,
+        backend,
+        confirm,
+        dry_run,
+        options,
+        Arc::clone(&protection),
+    );
+    let entries = run_scan_ui(status_rx, result_rx, app, backend, protection)?;
 
     Ok(entries)
 }
+// 结果列表的排序方式（按 s 循环切换）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortMode {
+    Arrival,     // 扫描发现顺序
+    SizeDesc,    // 大小从大到小
+    ModifiedAsc, // 最后修改时间从旧到新
+    PathAsc,     // 路径字母序
+}
+
+impl SortMode {
+    fn next(self) -> Self {
+        match self {
+            SortMode::Arrival => SortMode::SizeDesc,
+            SortMode::SizeDesc => SortMode::ModifiedAsc,
+            SortMode::ModifiedAsc => SortMode::PathAsc,
+            SortMode::PathAsc => SortMode::Arrival,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SortMode::Arrival => "发现顺序",
+            SortMode::SizeDesc => "大小 ↓",
+            SortMode::ModifiedAsc => "修改时间 ↑",
+            SortMode::PathAsc => "路径 A-Z",
+        }
+    }
+
+    fn compare(self, a: &FileEntry, b: &FileEntry) -> std::cmp::Ordering {
+        match self {
+            SortMode::Arrival => std::cmp::Ordering::Equal,
+            SortMode::SizeDesc => b.size_raw.cmp(&a.size_raw),
+            SortMode::ModifiedAsc => a.last_modified.cmp(&b.last_modified),
+            SortMode::PathAsc => a.path.cmp(&b.path),
+        }
+    }
+}
+
+// 搜索栏的匹配方式（输入时按 Tab 切换）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SearchMode {
+    Substring, // 子串匹配（忽略大小写）
+    Fuzzy,     // 模糊匹配：字符按顺序出现即可
+}
+
+impl SearchMode {
+    fn label(self) -> &'static str {
+        match self {
+            SearchMode::Substring => "子串",
+            SearchMode::Fuzzy => "模糊",
+        }
+    }
+
+    fn matches(self, path: &str, query: &str) -> bool {
+        match self {
+            SearchMode::Substring => path.to_lowercase().contains(&query.to_lowercase()),
+            SearchMode::Fuzzy => fuzzy_match(path, query),
+        }
+    }
+}
+
+// TUI 的全部状态
+struct ScanApp {
+    status: ScanStatus,
+    entries: Vec<FileEntry>,
+    // 当前可见条目在 entries 中的下标，list_state 的选中项对应这里
+    view: Vec<usize>,
+    list_state: ListState,
+    // 动画帧计数器
+    frame_count: u64,
+    start_time: Instant,
+    // 干运行模式：空格键只标记条目，不实际删除（按 d 切换）
+    dry_run: bool,
+    // 以 --dry-run 启动：始终为干运行，不能切换到实际删除
+    dry_run_locked: bool,
+    // 隐藏近期活跃项目中的条目（按 o 切换）
+    hide_recent: bool,
+    older_than: Duration,
+    // 本次会话删除成功后实际释放的字节数
+    space_saved: u64,
+    // 只对 view 排序，entries 的下标保持不变，删除结果按下标回传时仍能对应到正确的条目
+    sort_mode: SortMode,
+    // 多选：被选中条目在 entries 中的下标（按 m / a / i / f 修改，回车批量删除）
+    selection: BTreeSet<usize>,
+    backend: DeleteBackend,
+    // 删除前弹出确认框（--no-confirm / --force 时关闭）
+    confirm: bool,
+    // 等待确认的删除条目，不为空时显示确认框
+    pending_delete: Option<Vec<usize>>,
+    // 等待确认的条目中会被安全检查拒绝的路径及原因，打开确认框时检查一次
+    pending_refused: Vec<String>,
+    // 删除前的安全检查，确认框中预先列出会被拒绝的路径
+    protection: Arc<Protection>,
+    // 确认框中用来说明高风险条目的检测器
+    detectors: Registry,
+    // 大小列、排序和可释放空间使用的大小（条目的 size_raw 已按它计算）
+    size_mode: SizeMode,
+    // 按 / 打开搜索栏，按路径过滤 view
+    search_query: String,
+    search_input: bool,
+    search_mode: SearchMode,
+}
+
+impl ScanApp {
+    fn new(
+        older_than: Option<Duration>,
+        backend: DeleteBackend,
+        confirm: bool,
+        dry_run: bool,
+        options: &ScanOptions,
+        protection: Arc<Protection>,
+    ) -> Self {
+        Self {
+            status: ScanStatus::Scanning {
+                current_path: "初始化扫描...".to_string(),
+                progress: 0,
+                total_items: 0,
+                processed_items: 0,
+            },
+            entries: Vec::new(),
+            view: Vec::new(),
+            list_state: ListState::default().with_selected(Some(0)),
+            frame_count: 0,
+            start_time: Instant::now(),
+            dry_run,
+            dry_run_locked: dry_run,
+            hide_recent: older_than.is_some(),
+            older_than: older_than.unwrap_or(DEFAULT_OLDER_THAN),
+            space_saved: 0,
+            sort_mode: SortMode::Arrival,
+            selection: BTreeSet::new(),
+            backend,
+            confirm,
+            pending_delete: None,
+            pending_refused: Vec::new(),
+            protection,
+            detectors: options.detectors.clone(),
+            size_mode: options.size_mode,
+            search_query: String::new(),
+            search_input: false,
+            search_mode: SearchMode::Substring,
+        }
+    }
+
+    // 新条目加入列表；项目遍历完成后补全该项目所有条目的活跃时间，排序和过滤随之更新
+    fn push_event(&mut self, event: ScanEvent) {
+        match event {
+            ScanEvent::Entry(entry) => self.entries.push(entry),
+            ScanEvent::Activity(project) => {
+                for entry in &mut self.entries {
+                    if entry.project_path == project.project_path {
+                        entry.set_activity(project.project_activity);
+                    }
+                }
+            }
+        }
+        self.rebuild_view();
+    }
+
+    // 重新计算可见条目，保持选中项指向同一个条目
+    fn rebuild_view(&mut self) {
+        let selected = self.selected_index();
+        self.view = (0..self.entries.len())
+            .filter(|&i| !self.hide_recent || self.entries[i].is_stale(self.older_than))
+            .filter(|&i| {
+                self.search_query.is_empty()
+                    || self
+                        .search_mode
+                        .matches(&self.entries[i].path, &self.search_query)
+            })
+            .collect();
+        let (entries, mode) = (&self.entries, self.sort_mode);
+        // 稳定排序，相同键时保持发现顺序
+        self.view
+            .sort_by(|&a, &b| mode.compare(&entries[a], &entries[b]));
+        let position = selected
+            .and_then(|index| self.view.iter().position(|&i| i == index))
+            .unwrap_or(0);
+        self.list_state.select(Some(position));
+    }
+
+    // 选中项在 entries 中的下标
+    fn selected_index(&self) -> Option<usize> {
+        self.list_state
+            .selected()
+            .and_then(|i| self.view.get(i).copied())
+    }
+
+    // 只有还在原位置、不在删除中且不是只报告的条目可以加入多选和删除
+    fn is_selectable(&self, index: usize) -> bool {
+        let entry = &self.entries[index];
+        !entry.report_only
+            && matches!(
+                entry.delete_status,
+                DeleteStatus::NotDeleted | DeleteStatus::Marked | DeleteStatus::Failed(_)
+            )
+    }
+
+    // m：切换当前条目的选中状态
+    fn toggle_selection(&mut self) {
+        if let Some(index) = self.selected_index() {
+            if !self.selection.remove(&index) && self.is_selectable(index) {
+                self.selection.insert(index);
+            }
+        }
+    }
+
+    // a：选中当前视图中的全部条目
+    fn select_all(&mut self) {
+        let visible: Vec<usize> = self.view.clone();
+        for index in visible {
+            if self.is_selectable(index) {
+                self.selection.insert(index);
+            }
+        }
+    }
+
+    // i：反选当前视图中的条目，视图之外的选中项不受影响
+    fn invert_selection(&mut self) {
+        let visible: Vec<usize> = self.view.clone();
+        for index in visible {
+            if !self.selection.remove(&index) && self.is_selectable(index) {
+                self.selection.insert(index);
+            }
+        }
+    }
+
+    // f：按过滤条件选中，即当前视图中超过 older_than 未活跃的项目
+    fn select_stale(&mut self) {
+        let visible: Vec<usize> = self.view.clone();
+        for index in visible {
+            if self.is_selectable(index) && self.entries[index].is_stale(self.older_than) {
+                self.selection.insert(index);
+            }
+        }
+    }
+
+    // 删除选中条目实际能释放的空间，不含共享的硬链接
+    fn selection_size(&self) -> u64 {
+        self.selection
+            .iter()
+            .map(|&i| self.entries[i].size_exclusive)
+            .sum()
+    }
+
+    // 请求删除：需要确认时先弹出确认框，干运行的标记操作不需要确认
+    fn request_delete(&mut self, indices: Vec<usize>, pool: &DeletePool) {
+        let indices: Vec<usize> = indices
+            .into_iter()
+            .filter(|&i| self.is_selectable(i))
+            .collect();
+        if self.dry_run || !self.confirm {
+            self.start_delete(&indices, pool);
+            return;
+        }
+        if !indices.is_empty() {
+            self.pending_refused = indices
+                .iter()
+                .filter_map(|&i| {
+                    self.protection
+                        .check(Path::new(&self.entries[i].path))
+                        .err()
+                })
+                .collect();
+            self.pending_delete = Some(indices);
+        }
+    }
+
+    // 删除（或在干运行模式下标记）给定的条目，实际删除交给线程池执行
+    fn start_delete(&mut self, indices: &[usize], pool: &DeletePool) {
+        for &index in indices {
+            let entry = &mut self.entries[index];
+            match entry.delete_status {
+                DeleteStatus::NotDeleted | DeleteStatus::Marked | DeleteStatus::Failed(_)
+                    if self.dry_run =>
+                {
+                    entry.delete_status = DeleteStatus::Marked;
+                }
+                DeleteStatus::NotDeleted | DeleteStatus::Marked | DeleteStatus::Failed(_) => {
+                    log::info!("删除选中项: {:?}", entry.path);
+                    entry.delete_status = DeleteStatus::Deleting;
+                    pool.submit(index, entry.path.clone());
+                }
+                _ => log::info!("条目已删除或正在删除: {:?}", entry.path),
+            }
+            self.selection.remove(&index);
+        }
+    }
+}
+
 // 运行扫描UI
 fn run_scan_ui(
     status_rx: Receiver<ScanStatus>,
-    entries_rx: Receiver<FileEntry>,
+    entries_rx: Receiver<ScanEvent>,
+    mut app: ScanApp,
+    backend: DeleteBackend,
+    protection: Arc<Protection>,
 ) -> color_eyre::Result<Vec<FileEntry>> {
     color_eyre::install()?;
 
-    let mut current_status = ScanStatus::Scanning {
-        current_path: "初始化扫描...".to_string(),
-        progress: 0,
-        total_items: 0,
-        processed_items: 0,
-    };
-
-    // 存储扫描结果
-    let mut entries = Vec::new();
-    let mut list_state = ListState::default().with_selected(Some(0));
     // 添加删除结果通道
-    let (delete_tx, delete_rx) = mpsc::channel::<(usize, Result<(), String>)>();
-    // 动画帧计数器
-    let mut frame_count = 0;
-    let start_time = Instant::now();
+    let (delete_tx, delete_rx) = mpsc::channel::<DeleteResult>();
+    let pool = DeletePool::new(backend, protection, delete_tx)?;
     let mut last_update_time = Instant::now();
     let update_interval = Duration::from_millis(100); // 每100ms更新一次
     let poll_timeout = Duration::from_millis(10); // 事件轮询超时时间
 
     ratatui::run(|terminal| loop {
         // 检查是否有新的状态更新
-        let previous_status = current_status.clone();
+        let previous_status = app.status.clone();
         if let Ok(status) = status_rx.try_recv() {
-            current_status = status.clone();
+            app.status = status.clone();
             log::info!("接收数据:{:?}", status);
 
             // 如果状态从扫描变为完成，立即更新UI
             if matches!(previous_status, ScanStatus::Scanning { .. })
-                && matches!(current_status, ScanStatus::Completed { .. })
+                && matches!(app.status, ScanStatus::Completed { .. })
             {
-                terminal.draw(|frame| {
-                    render_scan_ui(
-                        frame,
-                        &current_status,
-                        frame_count,
-                        start_time,
-                        &entries,
-                        &mut list_state,
-                    );
-                })?;
+                terminal.draw(|frame| render_scan_ui(frame, &mut app))?;
             }
         }
 
         // 检查是否有新的条目
         let mut has_new_entries = false;
-        while let Ok(entry) = entries_rx.try_recv() {
-            entries.push(entry);
+        while let Ok(event) = entries_rx.try_recv() {
+            app.push_event(event);
             has_new_entries = true;
         }
         // 如果有新条目且状态是扫描中，立即更新UI
-        if has_new_entries && matches!(current_status, ScanStatus::Scanning { .. }) {
-            terminal.draw(|frame| {
-                render_scan_ui(
-                    frame,
-                    &current_status,
-                    frame_count,
-                    start_time,
-                    &entries,
-                    &mut list_state,
-                );
-            })?;
+        if has_new_entries && matches!(app.status, ScanStatus::Scanning { .. }) {
+            terminal.draw(|frame| render_scan_ui(frame, &mut app))?;
         }
         // 检查是否有删除结果
         let mut has_delete_results = false;
         while let Ok((index, result)) = delete_rx.try_recv() {
             has_delete_results = true;
-            if let Some(entry) = entries.get_mut(index) {
+            if let Some(entry) = app.entries.get_mut(index) {
                 match result {
-                    Ok(_) => {
-                        // 删除成功，标记为已删除
-                        entry.delete_status = DeleteStatus::Deleted;
+                    Ok(status) => {
+                        // 删除成功，标记为已删除或已移入回收站；只有真正删除才释放空间，
+                        // 与其他位置共享的硬链接不会释放
+                        if matches!(status, DeleteStatus::Deleted) {
+                            app.space_saved += entry.size_exclusive;
+                        }
+                        entry.delete_status = status;
                         log::info!("删除成功: {:?}", entry.path);
                     }
                     Err(e) => {
-                        // 删除失败，恢复为未删除状态
-                        entry.delete_status = DeleteStatus::NotDeleted;
-                        log::info!("删除失败: {:?}", e);
+                        // 删除失败或被安全检查拒绝，显示为 [FAILED]，选中时在列表底部显示原因
+                        log::info!("删除失败: {:?}: {}", entry.path, e);
+                        entry.delete_status = DeleteStatus::Failed(e);
                     }
                 }
             }
@@ -167,126 +766,168 @@ fn run_scan_ui(
         // 如果有新条目且状态是扫描中，立即更新UI
         if has_delete_results {
             log::info!("删除成功之后进行更新");
-            terminal.draw(|frame| {
-                render_scan_ui(
-                    frame,
-                    &current_status,
-                    frame_count,
-                    start_time,
-                    &entries,
-                    &mut list_state,
-                );
-            })?;
+            terminal.draw(|frame| render_scan_ui(frame, &mut app))?;
         }
 
         // 根据状态决定是否需要定期更新UI
-        let needs_periodic_update = matches!(current_status, ScanStatus::Scanning { .. });
+        let needs_periodic_update = matches!(app.status, ScanStatus::Scanning { .. });
 
         let now = Instant::now();
         // 渲染UI
         if needs_periodic_update && now.duration_since(last_update_time) >= update_interval {
             last_update_time = now;
-            frame_count += 1;
+            app.frame_count += 1;
 
             // 渲染UI
-            terminal.draw(|frame| {
-                render_scan_ui(
-                    frame,
-                    &current_status,
-                    frame_count,
-                    start_time,
-                    &entries,
-                    &mut list_state,
-                );
-            })?;
+            terminal.draw(|frame| render_scan_ui(frame, &mut app))?;
         }
 
         // 使用poll而不是read来检查按键事件，避免阻塞
         if event::poll(poll_timeout)? {
             if let event::Event::Key(key) = event::read()? {
                 if key.kind == KeyEventKind::Press {
+                    // 确认框打开时只响应 y / n / Esc
+                    if let Some(indices) = app.pending_delete.take() {
+                        match key.code {
+                            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                                app.start_delete(&indices, &pool)
+                            }
+                            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                                log::info!("取消删除 {} 个条目", indices.len());
+                            }
+                            _ => app.pending_delete = Some(indices),
+                        }
+                        terminal.draw(|frame| render_scan_ui(frame, &mut app))?;
+                        continue;
+                    }
+                    // 搜索栏打开时，字符键输入到搜索框，上下键仍可移动选中项
+                    if app.search_input {
+                        match key.code {
+                            KeyCode::Char(c) => app.search_query.push(c),
+                            KeyCode::Backspace => {
+                                app.search_query.pop();
+                            }
+                            KeyCode::Tab => {
+                                app.search_mode = match app.search_mode {
+                                    SearchMode::Substring => SearchMode::Fuzzy,
+                                    SearchMode::Fuzzy => SearchMode::Substring,
+                                };
+                            }
+                            KeyCode::Enter => app.search_input = false,
+                            KeyCode::Esc => {
+                                // 取消搜索，恢复完整列表
+                                app.search_input = false;
+                                app.search_query.clear();
+                            }
+                            KeyCode::Down if !app.view.is_empty() => app.list_state.select_next(),
+                            KeyCode::Up if !app.view.is_empty() => app.list_state.select_previous(),
+                            _ => {}
+                        }
+                        app.rebuild_view();
+                        terminal.draw(|frame| render_scan_ui(frame, &mut app))?;
+                        continue;
+                    }
                     let mut needs_render = false;
                     match key.code {
-                        KeyCode::Char('j') | KeyCode::Down => {
-                            // 检查是否有条目
-                            if !entries.is_empty() {
-                                list_state.select_next();
-                                needs_render = true;
+                        // 检查是否有条目
+                        KeyCode::Char('j') | KeyCode::Down if !app.view.is_empty() => {
+                            app.list_state.select_next();
+                            needs_render = true;
+                        }
+                        KeyCode::Char('k') | KeyCode::Up if !app.view.is_empty() => {
+                            app.list_state.select_previous();
+                            needs_render = true;
+                            // 确保选中索引有效
+                            if let Some(selected) = app.selected_index() {
+                                log::info!("选中项: {:?}", app.entries[selected].path);
                             }
                         }
-                        KeyCode::Char('k') | KeyCode::Up => {
-                            // 检查是否有条目
-                            if !entries.is_empty() {
-                                list_state.select_previous();
-                                needs_render = true;
-                                // 确保选中索引有效
-                                if let Some(selected) = list_state.selected() {
-                                    log::info!("选中项: {:?}", entries[selected].path);
+                        KeyCode::Char(' ') => {
+                            // 空格键删除选中项；干运行模式下切换标记
+                            if let Some(selected) = app.selected_index() {
+                                let entry = &mut app.entries[selected];
+                                if app.dry_run
+                                    && matches!(entry.delete_status, DeleteStatus::Marked)
+                                {
+                                    entry.delete_status = DeleteStatus::NotDeleted;
+                                } else {
+                                    app.request_delete(vec![selected], &pool);
                                 }
+                                needs_render = true;
                             }
                         }
-                        KeyCode::Char(' ') => {
-                            // 空格键删除选中项
-                            if let Some(selected) = list_state.selected() {
-                                if selected < entries.len() {
-                                    let entry = &mut entries[selected];
-                                    log::info!("删除选中项: {:?}", entry);
-                                    // 根据删除状态执行不同操作
-                                    match entry.delete_status {
-                                        DeleteStatus::NotDeleted => {
-                                            // 未删除，执行删除操作
-                                            entry.delete_status = DeleteStatus::Deleting;
-                                            needs_render = true;
-                                            // 克隆必要的值，用于后台线程
-                                            let path = entry.path.clone();
-                                            let index = selected;
-                                            let delete_tx_clone = delete_tx.clone();
-                                            // 在后台线程中执行删除操作
-                                            thread::spawn(move || {
-                                                match fs::remove_dir_all(&path) {
-                                                    Ok(_) => {
-                                                        // 删除成功
-                                                        let _ =
-                                                            delete_tx_clone.send((index, Ok(())));
-                                                    }
-                                                    Err(e) => {
-                                                        // 删除失败
-                                                        let _ = delete_tx_clone
-                                                            .send((index, Err(e.to_string())));
-                                                    }
-                                                }
-                                            });
-                                        }
-                                        DeleteStatus::Deleting => {
-                                            entry.delete_status = DeleteStatus::Deleting;
-
-                                            // 删除中，不做任何操作
-                                            log::info!("条目正在删除中: {:?}", entry.path);
-                                        }
-                                        DeleteStatus::Deleted => {
-                                            // 已删除，恢复
-                                            log::info!("这个已经删除过了: {:?}", entry.path);
-                                            needs_render = true;
+                        KeyCode::Enter if !app.selection.is_empty() => {
+                            // 回车批量删除全部多选条目
+                            let indices: Vec<usize> = app.selection.iter().copied().collect();
+                            app.request_delete(indices, &pool);
+                            needs_render = true;
+                        }
+                        KeyCode::Char('m') => {
+                            app.toggle_selection();
+                            needs_render = true;
+                        }
+                        KeyCode::Char('a') => {
+                            app.select_all();
+                            needs_render = true;
+                        }
+                        KeyCode::Char('i') => {
+                            app.invert_selection();
+                            needs_render = true;
+                        }
+                        KeyCode::Char('f') => {
+                            app.select_stale();
+                            needs_render = true;
+                        }
+                        KeyCode::Char('u') => {
+                            // u 键撤销隔离，把目录移回原位置
+                            if let Some(entry) =
+                                app.selected_index().and_then(|i| app.entries.get_mut(i))
+                            {
+                                if let DeleteStatus::Quarantined(id) = &entry.delete_status {
+                                    match quarantine::restore(id) {
+                                        Ok(_) => {
+                                            log::info!("已恢复: {:?}", entry.path);
+                                            entry.delete_status = DeleteStatus::NotDeleted;
                                         }
+                                        Err(e) => log::info!("恢复失败: {:?}: {}", entry.path, e),
                                     }
+                                    needs_render = true;
                                 }
                             }
                         }
-                        KeyCode::Char('q') | KeyCode::Esc => break Ok(entries),
+                        KeyCode::Char('d') if !app.dry_run_locked => {
+                            // 切换干运行模式；不用 n，避免取消确认框时多按一次就切换了模式
+                            app.dry_run = !app.dry_run;
+                            needs_render = true;
+                        }
+                        KeyCode::Char('o') => {
+                            // 切换是否隐藏近期活跃项目
+                            app.hide_recent = !app.hide_recent;
+                            app.rebuild_view();
+                            needs_render = true;
+                        }
+                        KeyCode::Char('s') => {
+                            // 循环切换排序方式，选中项保持不变
+                            app.sort_mode = app.sort_mode.next();
+                            app.rebuild_view();
+                            needs_render = true;
+                        }
+                        KeyCode::Char('/') => {
+                            app.search_input = true;
+                            needs_render = true;
+                        }
+                        KeyCode::Esc if !app.search_query.is_empty() => {
+                            // 有搜索条件时 Esc 先清除过滤
+                            app.search_query.clear();
+                            app.rebuild_view();
+                            needs_render = true;
+                        }
+                        KeyCode::Char('q') | KeyCode::Esc => break Ok(app.entries),
                         _ => {}
                     }
                     // 如果需要渲染，立即更新UI
                     if needs_render {
-                        terminal.draw(|frame| {
-                            render_scan_ui(
-                                frame,
-                                &current_status,
-                                frame_count,
-                                start_time,
-                                &entries,
-                                &mut list_state,
-                            );
-                        })?;
+                        terminal.draw(|frame| render_scan_ui(frame, &mut app))?;
                     }
                 }
             }
@@ -295,19 +936,17 @@ fn run_scan_ui(
 }
 
 // 渲染扫描UI
-fn render_scan_ui(
-    frame: &mut Frame,
-    status: &ScanStatus,
-    frame_count: u64,
-    start_time: Instant,
-    entries: &[FileEntry],
-    list_state: &mut ListState,
-) {
-    // 计算总大小
-    let total_size: u64 = entries.iter().map(|e| e.size_raw).sum();
+fn render_scan_ui(frame: &mut Frame, app: &mut ScanApp) {
+    let entries = &app.entries;
+    // 计算可释放空间，不包含已经删除（或移走）、只报告的条目和共享的硬链接
+    let total_size: u64 = entries
+        .iter()
+        .filter(|e| !e.delete_status.is_removed() && !e.report_only)
+        .map(|e| e.size_exclusive)
+        .sum();
     let releasable_space = human_readable_size(total_size);
-    let space_saved = "0.00 GB".to_string();
-    let elapsed = start_time.elapsed();
+    let space_saved = human_readable_size(app.space_saved);
+    let elapsed = app.start_time.elapsed();
     let search_time = format!("{:.2}s", elapsed.as_secs_f64());
 
     // 主布局：上（头部）、中（列表区域）、下（底部栏）
@@ -347,7 +986,25 @@ fn render_scan_ui(
             Span::styled("Space saved: ", Style::default().fg(Color::Gray)),
             Span::styled(space_saved, Style::default().fg(Color::White)),
         ]),
-        Line::from(""),
+        Line::from(vec![
+            Span::styled("Selected: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                format!(
+                    "{} items / {}",
+                    app.selection.len(),
+                    human_readable_size(app.selection_size())
+                ),
+                Style::default().fg(Color::White),
+            ),
+            if app.dry_run {
+                Span::styled(
+                    "  DRY RUN (space marks only)",
+                    Style::default().fg(Color::Magenta),
+                )
+            } else {
+                Span::raw("")
+            },
+        ]),
         Line::from(vec![
             Span::styled("Search completed ", Style::default().fg(Color::Green)),
             Span::styled(search_time, Style::default().fg(Color::Cyan)),
@@ -357,7 +1014,7 @@ fn render_scan_ui(
     frame.render_widget(info, header_layout[1]);
 
     // ========== 中部区域 ==========
-    match status {
+    match &app.status {
         ScanStatus::Scanning {
             current_path,
             progress,
@@ -375,7 +1032,7 @@ fn render_scan_ui(
                 .split(main_layout[1]);
 
             let spinner_chars = ['-', '\\', '|', '/'];
-            let spinner_index = (frame_count / 2) as usize % spinner_chars.len();
+            let spinner_index = (app.frame_count / 2) as usize % spinner_chars.len();
             let duration_str = format!(
                 "{:02}:{:02}",
                 elapsed.as_secs() / 60,
@@ -411,22 +1068,78 @@ fn render_scan_ui(
                 .wrap(Wrap { trim: true });
             frame.render_widget(path_text, scan_layout[2]);
         }
-        ScanStatus::Completed { .. } => {
-            let list_block = Block::default()
-                .borders(Borders::ALL)
-                .title(format!("扫描结果 ({} items)", entries.len()));
+        ScanStatus::Completed {
+            excluded,
+            skipped_mounts,
+            ..
+        } => {
+            let mut title = if app.view.len() == entries.len() {
+                format!("扫描结果 ({} items", entries.len())
+            } else {
+                format!("扫描结果 ({} of {} items", app.view.len(), entries.len())
+            };
+            if app.hide_recent {
+                let recent = entries
+                    .iter()
+                    .filter(|e| !e.is_stale(app.older_than))
+                    .count();
+                title.push_str(&format!(
+                    ", 已隐藏 {} 天内活跃的 {} 项",
+                    app.older_than.as_secs() / 86400,
+                    recent
+                ));
+            }
+            if *excluded > 0 {
+                title.push_str(&format!(", 已排除 {} 个目录", excluded));
+            }
+            if !skipped_mounts.is_empty() {
+                title.push_str(&format!(", 跳过 {} 个挂载点", skipped_mounts.len()));
+            }
+            title.push_str(&format!(") 排序: {}", app.sort_mode.label()));
+            let list_block = Block::default().borders(Borders::ALL).title(title);
 
             frame.render_widget(list_block.clone(), main_layout[1]);
 
             let inner_area = list_block.inner(main_layout[1]); // 获取内部可用区域
 
+            let show_search = app.search_input || !app.search_query.is_empty();
+            // 选中的条目删除失败时显示原因
+            let failure = app
+                .selected_index()
+                .and_then(|i| match &entries[i].delete_status {
+                    DeleteStatus::Failed(e) => Some(e.as_str()),
+                    _ => None,
+                });
             let inner_layout = Layout::default()
                 .direction(Direction::Vertical)
                 .constraints([
-                    Constraint::Length(1), // 表头
-                    Constraint::Fill(1),   // 列表
+                    Constraint::Length(1),                        // 表头
+                    Constraint::Fill(1),                          // 列表
+                    Constraint::Length(failure.is_some() as u16), // 失败原因
+                    Constraint::Length(show_search as u16),       // 搜索栏
                 ])
                 .split(inner_area);
+            if let Some(reason) = failure {
+                let line = Line::from(Span::styled(
+                    format!("删除失败: {}", reason),
+                    Style::default().fg(Color::Red),
+                ));
+                frame.render_widget(Paragraph::new(line), inner_layout[2]);
+            }
+            if show_search {
+                let mut search_line = vec![
+                    Span::styled("/", Style::default().fg(Color::Yellow)),
+                    Span::raw(app.search_query.as_str()),
+                ];
+                if app.search_input {
+                    search_line.push(Span::styled("█", Style::default().fg(Color::Gray)));
+                }
+                search_line.push(Span::styled(
+                    format!("  [{}, Tab 切换]", app.search_mode.label()),
+                    Style::default().fg(Color::Gray),
+                ));
+                frame.render_widget(Paragraph::new(Line::from(search_line)), inner_layout[3]);
+            }
             // 表头（与列表列宽对齐）
             let list_width = main_layout[1].width.saturating_sub(2); // 减去边框
             let path_width = list_width.saturating_sub(30);
@@ -449,7 +1162,7 @@ fn render_scan_ui(
                 ),
                 Span::raw("  "),
                 Span::styled(
-                    format!("{:>width$}", "Size", width = size_width),
+                    format!("{:>width$}", app.size_mode.label(), width = size_width),
                     Style::default()
                         .fg(Color::White)
                         .add_modifier(Modifier::BOLD),
@@ -463,20 +1176,16 @@ fn render_scan_ui(
             let list_area = inner_layout[1];
 
             // 列宽定义（与底部表头对齐）
-            let path_width = list_area.width.saturating_sub(10); // 剩余空间给 Path
+            let path_width = list_area.width.saturating_sub(30); // 剩余空间给 Path
             let last_mod_width = 10;
             let size_width = 12;
 
-            let items: Vec<ListItem> = entries
+            let items: Vec<ListItem> = app
+                .view
                 .iter()
-                .enumerate()
-                .map(|(_i, e)| {
+                .map(|&i| (app.selection.contains(&i), &entries[i]))
+                .map(|(picked, e)| {
                     log::info!("删除{:?}", e);
-                    let path_display = if e.path.len() > path_width as usize {
-                        format!("...{}", &e.path[e.path.len() - path_width as usize + 3..])
-                    } else {
-                        e.path.clone()
-                    };
                     // 根据删除状态添加不同的前缀
                     let status_prefix = match e.delete_status {
                         DeleteStatus::NotDeleted => Span::raw(""),
@@ -486,17 +1195,51 @@ fn render_scan_ui(
                         DeleteStatus::Deleted => {
                             Span::styled("[DELETED] ", Style::default().fg(Color::Green))
                         }
+                        DeleteStatus::Trashed => {
+                            Span::styled("[TRASHED] ", Style::default().fg(Color::Green))
+                        }
+                        DeleteStatus::Quarantined(_) => {
+                            Span::styled("[QUARANTINED] ", Style::default().fg(Color::LightBlue))
+                        }
+                        DeleteStatus::Marked => {
+                            Span::styled("[MARKED] ", Style::default().fg(Color::Magenta))
+                        }
+                        DeleteStatus::Failed(_) => {
+                            Span::styled("[FAILED] ", Style::default().fg(Color::Red))
+                        }
                     };
+                    let pick_marker = if picked {
+                        Span::styled("[*] ", Style::default().fg(Color::LightCyan))
+                    } else {
+                        Span::raw("")
+                    };
+                    let link_marker = if e.report_only {
+                        Span::styled("[REPORT ONLY] ", Style::default().fg(Color::DarkGray))
+                    } else if e.is_symlink() {
+                        Span::styled("[LINK] ", Style::default().fg(Color::LightYellow))
+                    } else {
+                        Span::raw("")
+                    };
+                    // 前缀占用路径列的宽度，保证后面的列仍与表头对齐
+                    let path_width = (path_width as usize).saturating_sub(
+                        pick_marker.width() + status_prefix.width() + link_marker.width(),
+                    );
+                    let path_display = truncate_start(&e.path, path_width);
+                    // 按显示宽度补齐，中文路径也能与后面的列对齐
+                    let display_width = Span::raw(path_display.as_str()).width();
                     let line = Line::from(vec![
+                        pick_marker,
                         status_prefix,
-                        Span::raw(format!(
-                            "{:<width$}",
-                            path_display,
-                            width = path_width as usize
-                        )),
+                        link_marker,
+                        Span::raw(path_display),
+                        Span::raw(" ".repeat(path_width.saturating_sub(display_width))),
                         Span::raw("  "),
                         Span::styled(
-                            format!("{:>width$}", e.size_display, width = last_mod_width),
+                            format!(
+                                "{:>width$}",
+                                e.last_modified.map_or_else(|| "-".to_string(), format_age),
+                                width = last_mod_width
+                            ),
                             Style::default().fg(Color::Gray),
                         ),
                         Span::raw("  "),
@@ -515,9 +1258,171 @@ fn render_scan_ui(
 
             let list = List::new(items)
                 .highlight_style(Style::default().bg(Color::Yellow).fg(Color::Black));
-            frame.render_stateful_widget(list, list_area, list_state);
+            frame.render_stateful_widget(list, list_area, &mut app.list_state);
+        }
+    }
+
+    if let Some(indices) = &app.pending_delete {
+        render_confirm_dialog(frame, app, indices);
+    }
+}
+
+// 确认框中最多逐条列出的被拒绝路径数
+const MAX_REFUSED_SHOWN: usize = 5;
+
+// 删除确认框：列出将要删除的路径、总大小和安全提示，按 y 确认
+fn render_confirm_dialog(frame: &mut Frame, app: &ScanApp, indices: &[usize]) {
+    let area = frame.area();
+    let max_height = (area.height * 4 / 5).max(10).min(area.height);
+
+    // 与可释放空间一致，按删除后实际释放的大小统计
+    let total: u64 = indices.iter().map(|&i| app.entries[i].size_exclusive).sum();
+    let action = match app.backend {
+        DeleteBackend::Remove => "永久删除",
+        DeleteBackend::Trash => "移入回收站",
+        DeleteBackend::Quarantine => "移入隔离区",
+    };
+    let mut warnings = Vec::new();
+    if app.backend == DeleteBackend::Remove {
+        warnings.push("直接删除后无法恢复，可使用 --trash 或 --quarantine".to_string());
+    }
+    let recent = indices
+        .iter()
+        .filter(|&&i| !app.entries[i].is_stale(app.older_than))
+        .count();
+    if recent > 0 {
+        warnings.push(format!(
+            "{} 个目录所属项目在 {} 天内有修改",
+            recent,
+            app.older_than.as_secs() / 86400
+        ));
+    }
+    let shared: u64 = indices.iter().map(|&i| app.entries[i].size_shared).sum();
+    if shared > 0 {
+        warnings.push(format!(
+            "其中 {} 为与其他位置共享的硬链接，删除后可能不会释放",
+            human_readable_size(shared)
+        ));
+    }
+    let links = indices
+        .iter()
+        .filter(|&&i| app.entries[i].is_symlink())
+        .count();
+    if links > 0 {
+        warnings.push(format!(
+            "{} 个为符号链接，只删除链接本身，不影响其指向的目录",
+            links
+        ));
+    }
+    // 安全检查会拒绝的路径：确认后这些条目会显示为 [FAILED]
+    if !app.pending_refused.is_empty() {
+        warnings.push(format!(
+            "{} 个目录将被安全检查拒绝:",
+            app.pending_refused.len()
+        ));
+        let shown = app.pending_refused.len().min(MAX_REFUSED_SHOWN);
+        warnings.extend(
+            app.pending_refused[..shown]
+                .iter()
+                .map(|e| format!("  {}", e)),
+        );
+        if app.pending_refused.len() > shown {
+            warnings.push(format!(
+                "  ... 还有 {} 项",
+                app.pending_refused.len() - shown
+            ));
         }
     }
+    // 中高风险的条目按检测器汇总说明
+    let mut risky: Vec<(&str, RiskLevel, usize)> = Vec::new();
+    for &i in indices {
+        let entry = &app.entries[i];
+        if entry.risk == RiskLevel::Low {
+            continue;
+        }
+        match risky
+            .iter_mut()
+            .find(|(name, _, _)| *name == entry.matched_pattern)
+        {
+            Some((_, _, count)) => *count += 1,
+            None => risky.push((&entry.matched_pattern, entry.risk, 1)),
+        }
+    }
+    for (name, risk, count) in risky {
+        let description = app
+            .detectors
+            .get(name)
+            .map_or("", |detector| detector.description());
+        warnings.push(format!(
+            "{} 个 {} 目录风险为{}: {}",
+            count, name, risk, description
+        ));
+    }
+
+    // 边框、标题行、空行、提示和操作行之外的空间用来列出路径
+    let max_paths = (max_height as usize)
+        .saturating_sub(6 + warnings.len())
+        .max(1);
+    let mut lines = vec![Line::from(vec![
+        Span::raw(format!("将{} {} 个目录，共 ", action, indices.len())),
+        Span::styled(
+            human_readable_size(total),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ),
+    ])];
+    if indices.len() > max_paths {
+        lines.extend(
+            indices[..max_paths - 1]
+                .iter()
+                .map(|&i| Line::from(format!("  {}", app.entries[i].path))),
+        );
+        lines.push(Line::from(format!(
+            "  ... 还有 {} 项",
+            indices.len() - max_paths + 1
+        )));
+    } else {
+        lines.extend(
+            indices
+                .iter()
+                .map(|&i| Line::from(format!("  {}", app.entries[i].path))),
+        );
+    }
+    lines.push(Line::from(""));
+    lines.extend(warnings.into_iter().map(|w| {
+        Line::from(Span::styled(
+            format!("⚠ {}", w),
+            Style::default().fg(Color::Yellow),
+        ))
+    }));
+    lines.push(Line::from(vec![
+        Span::styled(
+            "y",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(" 确认  "),
+        Span::styled("n / Esc", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(" 取消"),
+    ]));
+
+    // 按内容收缩高度，居中显示
+    let width = (area.width * 4 / 5).max(40).min(area.width);
+    let height = (lines.len() as u16 + 2).min(max_height);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    let dialog = Paragraph::new(Text::from(lines)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("确认删除")
+            .border_style(Style::default().fg(Color::Red)),
+    );
+    frame.render_widget(Clear, popup);
+    frame.render_widget(dialog, popup);
 }
 
 #[cfg(test)]
@@ -1,29 +1,133 @@
-#[derive(Debug, Clone)]
+use crate::detector::RiskLevel;
+use serde::{Serialize, Serializer};
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DeleteStatus {
-    NotDeleted, // 未删除
-    Deleting,   // 删除中
-    Deleted,    // 删除结束
+    NotDeleted,          // 未删除
+    Deleting,            // 删除中
+    Deleted,             // 删除结束
+    Trashed,             // 已移入回收站
+    Quarantined(String), // 已移入隔离区（隔离 id），可恢复
+    Marked,              // 干运行模式下标记为待删除
+    Failed(String),      // 删除失败及原因
+}
+
+impl DeleteStatus {
+    // 目录已不在原位置（已删除、已移入回收站或隔离区）
+    pub fn is_removed(&self) -> bool {
+        matches!(
+            self,
+            DeleteStatus::Deleted | DeleteStatus::Trashed | DeleteStatus::Quarantined(_)
+        )
+    }
+
+    // 机器可读的状态名，与 JSON 序列化保持一致
+    pub fn key(&self) -> &'static str {
+        match self {
+            DeleteStatus::NotDeleted => "not_deleted",
+            DeleteStatus::Deleting => "deleting",
+            DeleteStatus::Deleted => "deleted",
+            DeleteStatus::Trashed => "trashed",
+            DeleteStatus::Quarantined(_) => "quarantined",
+            DeleteStatus::Marked => "marked",
+            DeleteStatus::Failed(_) => "failed",
+        }
+    }
+}
+
+impl std::fmt::Display for DeleteStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeleteStatus::NotDeleted => write!(f, "未删除"),
+            DeleteStatus::Deleting => write!(f, "删除中"),
+            DeleteStatus::Deleted => write!(f, "已删除"),
+            DeleteStatus::Trashed => write!(f, "已移入回收站"),
+            DeleteStatus::Quarantined(id) => write!(f, "已隔离 ({})", id),
+            DeleteStatus::Marked => write!(f, "待删除"),
+            DeleteStatus::Failed(e) => write!(f, "删除失败: {}", e),
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct FileEntry {
     pub file_type: char,
     pub permissions: String,
     pub size_display: String,
-    pub size_raw: u64,
+    pub size_raw: u64,       // 按 --size-mode 选择的大小，用于显示和排序
+    pub size_apparent: u64,  // 文件长度之和
+    pub size_disk: u64,      // 实际占用的磁盘空间（分配的块数 × 512）
+    pub size_exclusive: u64, // 只删除该目录即可释放的大小（按 --size-mode），用于统计释放和可释放空间
+    pub size_shared: u64,    // 与其他位置硬链接共享、删除该目录不会释放的大小
     pub path: String,
+    pub matched_pattern: String, // 命中的检测器（如 cargo、npm，或 -d/--dir 指定的目录名）
+    pub risk: RiskLevel,         // 检测器给出的删除风险
+    #[serde(serialize_with = "serialize_time")]
+    pub last_modified: Option<SystemTime>, // 最后修改时间
+    pub project_path: String,    // 所属项目（含 Cargo.toml / package.json 的目录）
+    #[serde(serialize_with = "serialize_time")]
+    pub project_activity: Option<SystemTime>, // 项目源码（不含目标目录）最新的修改时间
+    pub report_only: bool, // 经 --follow-symlinks 跟随的链接找到、实际位于扫描根目录之外：只报告，不删除
     pub delete_status: DeleteStatus, // 使用枚举代替简单的布尔值
 }
 
-use clap::Parser;
+impl FileEntry {
+    // 目标本身是符号链接，删除时只移除链接
+    pub fn is_symlink(&self) -> bool {
+        self.file_type == 'l'
+    }
+
+    // 补全所属项目的活跃时间，最后修改时间同时计入项目源码的修改
+    pub fn set_activity(&mut self, activity: Option<SystemTime>) {
+        self.project_activity = activity;
+        self.last_modified = self.last_modified.max(activity);
+    }
+
+    // 所属项目在 older_than 时长内没有任何源码修改；活跃时间未知时不算过期，
+    // 避免 --older-than --force 删除无法确认是否活跃的项目
+    pub fn is_stale(&self, older_than: Duration) -> bool {
+        self.project_activity.is_some_and(|activity| {
+            SystemTime::now()
+                .duration_since(activity)
+                .is_ok_and(|age| age >= older_than)
+        })
+    }
+}
+
+// 项目遍历完成后才确定的活跃时间，适用于 project_path 相同的所有条目。
+// 条目统计完大小就先产出，活跃时间随后单独补充
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectActivity {
+    pub project_path: String,
+    #[serde(serialize_with = "serialize_time")]
+    pub project_activity: Option<SystemTime>,
+}
+
+// 将时间序列化为 RFC 3339 字符串，便于 jq / 表格处理
+fn serialize_time<S: Serializer>(time: &Option<SystemTime>, s: S) -> Result<S::Ok, S::Error> {
+    match time {
+        Some(t) => s.serialize_some(&format_time(*t)),
+        None => s.serialize_none(),
+    }
+}
+
+pub fn format_time(time: SystemTime) -> String {
+    chrono::DateTime::<chrono::Local>::from(time).to_rfc3339()
+}
+
+use crate::utils::parse_duration;
+use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Parser, Debug)]
 #[command(
     version,
     author,
     about = "一个用于清理项目目录的工具",
-    long_about = "用法示例:\n  clean -d node_modules 删除 node_modules 目录\n  clean -d target 删除 target 目录\n  clean -d node_modules -d target 同时删除多个目录\n  clean -s pattern 交互式搜索并显示结果"
+    long_about = "用法示例:\n  clean -d node_modules 删除 node_modules 目录\n  clean -d target 删除 target 目录\n  clean -d node_modules -d target 同时删除多个目录\n  clean -p python,web 清理 Python 和前端框架的缓存\n  clean -s pattern 交互式搜索并显示结果"
 )]
 pub struct Cli {
     /// 指定要清理的根目录
@@ -31,11 +135,25 @@ pub struct Cli {
     pub dir: String,
 
     /// 指定要删除的目录名称
-    #[arg(short = 'd', long = "dir", value_name = "NAME",
-    default_values_t = vec!["target".to_string(), "node_modules".to_string()],
-    help = "指定要删除的目录名称（默认: target, node_modules）")]
+    #[arg(
+        short = 'd',
+        long = "dir",
+        value_name = "NAME",
+        help = "指定要删除的目录名称（按名称精确匹配；与 --preset 都未指定时使用 rust + node）"
+    )]
     pub dirs_to_delete: Vec<String>,
 
+    /// 按生态选择内置的检测器
+    #[arg(
+        short = 'p',
+        long = "preset",
+        value_enum,
+        value_name = "PRESET",
+        value_delimiter = ',',
+        help = "按生态启用内置检测器，可重复或用逗号分隔；.venv、.next 等隐藏目录需要同时指定 --hidden"
+    )]
+    pub presets: Vec<Preset>,
+
     /// 显示将要删除的目录，但不实际删除（干运行模式）
     #[arg(
         short = 'n',
@@ -48,13 +166,215 @@ pub struct Cli {
     #[arg(short = 'v', long = "verbose")]
     pub verbose: bool,
 
-    /// 强制删除，不提示确认
-    #[arg(short = 'f', long = "force", help = "强制删除，不提示确认")]
+    /// 强制删除，不提示确认（无界面批量清理，适用于 cron / CI）
+    #[arg(
+        short = 'f',
+        long = "force",
+        visible_alias = "yes",
+        help = "强制删除，不提示确认（无界面批量清理）"
+    )]
     pub force: bool,
 
+    /// 删除前不弹出确认框（--force 时默认开启）
+    #[arg(long = "no-confirm", help = "删除前不弹出确认框（--force 隐含此选项）")]
+    pub no_confirm: bool,
+
+    /// 把带有合法 CACHEDIR.TAG 的目录也作为清理目标
+    #[arg(
+        long = "cachedir-tag",
+        help = "不论目录名，把带有合法 CACHEDIR.TAG 的目录也作为清理目标"
+    )]
+    pub cachedir_tag: bool,
+
+    /// 安全模式：只列出被 git 忽略的目录
+    #[arg(
+        long = "gitignored-only",
+        help = "安全模式：只列出位于 git 仓库内且被忽略规则（.gitignore 等）忽略的目录"
+    )]
+    pub gitignored_only: bool,
+
+    /// 受保护的路径，命中的目录及其子目录不会被删除
+    #[arg(
+        long = "protect",
+        value_name = "GLOB",
+        help = "受保护的路径 glob（可重复），与配置文件中的 protect 合并"
+    )]
+    pub protect: Vec<String>,
+
+    /// 不跨越文件系统
+    #[arg(
+        short = 'x',
+        long = "one-file-system",
+        help = "不进入挂载在扫描目录之下的其他文件系统（网络挂载、bind mount 等）"
+    )]
+    pub one_file_system: bool,
+
+    /// 扫描隐藏目录
+    #[arg(
+        long = "hidden",
+        help = "扫描以 . 开头的隐藏目录（如 .venv、.next、.cache），.git 内部始终跳过"
+    )]
+    pub hidden: bool,
+
+    /// 跟随指向目录的符号链接
+    #[arg(
+        long = "follow-symlinks",
+        help = "查找目标时进入指向目录的符号链接（会检测循环）；经链接找到的目标实际位于扫描根目录之外，只报告、不删除；目标本身是链接时只统计、只删除链接"
+    )]
+    pub follow_symlinks: bool,
+
+    /// 扫描时跳过的路径，整个子树都不会被读取
+    #[arg(
+        long = "exclude",
+        value_name = "GLOB",
+        help = "扫描时跳过的路径 glob（可重复；不含 / 时按目录名匹配，third_party/sdk 这样的相对路径相对于扫描根目录），与配置文件中的 exclude 合并"
+    )]
+    pub exclude: Vec<String>,
+
+    /// 配置文件路径
+    #[arg(
+        long = "config",
+        value_name = "PATH",
+        help = "配置文件路径（默认: ~/.config/rust_kill/config.toml）"
+    )]
+    pub config: Option<PathBuf>,
+
+    /// 只列出所属项目在指定时长内没有修改过的目录
+    #[arg(
+        long = "older-than",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        help = "只列出所属项目在指定时长内未被修改的目录（如 30d、2w、6mo）"
+    )]
+    pub older_than: Option<Duration>,
+
+    /// 移入回收站而不是直接删除
+    #[arg(
+        long = "trash",
+        help = "将目录移入回收站（freedesktop Trash 规范），而不是直接删除"
+    )]
+    pub trash: bool,
+
+    /// 移入隔离区，保留期内可恢复
+    #[arg(
+        long = "quarantine",
+        conflicts_with = "trash",
+        help = "将目录移入隔离区（同一文件系统内改名），保留期内可用 restore 恢复"
+    )]
+    pub quarantine: bool,
+
+    /// 隔离区保留期，超过后真正删除
+    #[arg(
+        long = "retention",
+        value_name = "DURATION",
+        default_value = "7d",
+        value_parser = parse_duration,
+        help = "隔离区保留期（如 7d、12h、2w），超过后真正删除"
+    )]
+    pub retention: Duration,
+
+    /// 大小的统计方式
+    #[arg(
+        long = "size-mode",
+        value_enum,
+        value_name = "MODE",
+        default_value = "disk",
+        help = "大小的统计方式：disk 为实际占用的磁盘空间（删除后真正释放的空间），apparent 为文件长度之和"
+    )]
+    pub size_mode: SizeMode,
+
+    /// 以机器可读格式输出扫描结果
+    #[arg(
+        long = "format",
+        value_enum,
+        value_name = "FORMAT",
+        help = "以机器可读格式输出扫描结果（json / ndjson / csv）"
+    )]
+    pub format: Option<OutputFormat>,
+
     /// 递归删除子目录中的匹配项
     #[arg(short = 'r', long = "recursive", help = "递归删除子目录中的匹配项")]
     pub recursive: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    // 删除前是否需要确认，--force 隐含 --no-confirm
+    pub fn confirm(&self) -> bool {
+        !(self.no_confirm || self.force)
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// 从隔离区恢复目录；不带参数时列出隔离区内容
+    Restore {
+        /// 隔离 id 或原路径
+        #[arg(value_name = "ID|PATH")]
+        target: Option<String>,
+    },
+}
+
+// 内置检测器的预设分组
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    /// node_modules（npm / yarn / pnpm）
+    Node,
+    /// Cargo 的 target 目录
+    Rust,
+    /// __pycache__；.venv、.tox、.mypy_cache 需要 --hidden
+    Python,
+    /// Gradle 的 build / .gradle（需要 --hidden），Maven 的 target
+    Jvm,
+    /// .NET 的 bin / obj
+    Dotnet,
+    /// Nuxt 的 .output；Next.js 的 .next、Nuxt 的 .nuxt 需要 --hidden
+    Web,
+    /// 含 CMakeCache.txt 和 CMakeFiles/ 的 out-of-source CMake 构建目录
+    Cmake,
+    /// 以上全部
+    All,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 7] = [
+        Preset::Node,
+        Preset::Rust,
+        Preset::Python,
+        Preset::Jvm,
+        Preset::Dotnet,
+        Preset::Web,
+        Preset::Cmake,
+    ];
+}
+
+// 大小的统计方式
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeMode {
+    /// 文件长度之和（ls / du --apparent-size）
+    Apparent,
+    /// 实际占用的磁盘空间（du）
+    Disk,
+}
+
+impl SizeMode {
+    // TUI 中大小列的表头
+    pub fn label(&self) -> &'static str {
+        match self {
+            SizeMode::Apparent => "Apparent",
+            SizeMode::Disk => "Disk",
+        }
+    }
+}
+
+// 机器可读的输出格式
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Json,
+    Ndjson, // 边扫描边输出；项目遍历完成后另起一行输出 {project_path, project_activity}
+    Csv,
 }
 
 #[derive(Debug)]
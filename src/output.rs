@@ -0,0 +1,82 @@
+use crate::models::{format_time, FileEntry, OutputFormat};
+use serde::Serialize;
+use std::io::{self, Write};
+
+const CSV_HEADER: &str = "path,matched_pattern,risk,size_raw,size_display,size_apparent,\
+size_disk,size_exclusive,size_shared,last_modified,project_path,project_activity,report_only,delete_status";
+
+// 将全部条目按指定格式写出
+pub fn write_entries<W: Write>(
+    out: &mut W,
+    format: OutputFormat,
+    entries: &[FileEntry],
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, entries)?;
+            writeln!(out)?;
+        }
+        OutputFormat::Ndjson => {
+            for entry in entries {
+                write_ndjson_line(out, entry)?;
+            }
+        }
+        OutputFormat::Csv => {
+            writeln!(out, "{}", CSV_HEADER)?;
+            for entry in entries {
+                write_csv_row(out, entry)?;
+            }
+        }
+    }
+    out.flush()
+}
+
+// 写出单行 NDJSON（条目或项目活跃时间），并立即刷新以便下游流式消费
+pub fn write_ndjson_line<W: Write, T: Serialize>(out: &mut W, record: &T) -> io::Result<()> {
+    serde_json::to_writer(&mut *out, record)?;
+    writeln!(out)?;
+    out.flush()
+}
+
+fn write_csv_row<W: Write>(out: &mut W, entry: &FileEntry) -> io::Result<()> {
+    let last_modified = entry.last_modified.map(format_time).unwrap_or_default();
+    let fields = [
+        entry.path.clone(),
+        entry.matched_pattern.clone(),
+        entry.risk.key().to_string(),
+        entry.size_raw.to_string(),
+        entry.size_display.clone(),
+        entry.size_apparent.to_string(),
+        entry.size_disk.to_string(),
+        entry.size_exclusive.to_string(),
+        entry.size_shared.to_string(),
+        last_modified,
+        entry.project_path.clone(),
+        entry.project_activity.map(format_time).unwrap_or_default(),
+        entry.report_only.to_string(),
+        entry.delete_status.key().to_string(),
+    ];
+    let row: Vec<String> = fields.iter().map(|f| csv_escape(f)).collect();
+    writeln!(out, "{}", row.join(","))
+}
+
+// 按 RFC 4180 转义：含逗号、引号或换行的字段用双引号包裹
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_escape_quotes_special_fields() {
+        assert_eq!(csv_escape("/tmp/a"), "/tmp/a");
+        assert_eq!(csv_escape("/tmp/a,b"), "\"/tmp/a,b\"");
+        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,55 @@
+use crate::dir_listing::print_entries_table;
+use crate::models::{FileEntry, OutputFormat};
+use crate::output;
+use crate::utils::human_readable_size;
+use std::io;
+
+// 删除计划：干运行模式下将要删除的目录以及可释放的空间
+#[derive(Debug, Clone, Default)]
+pub struct DeletionPlan {
+    pub entries: Vec<FileEntry>,
+    pub total_bytes: u64,
+}
+
+impl DeletionPlan {
+    pub fn new(mut entries: Vec<FileEntry>) -> Self {
+        // 按路径排序，保证输出稳定
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        // 与其他位置共享的硬链接删除后不会释放，不计入可释放空间
+        let total_bytes = entries.iter().map(|e| e.size_exclusive).sum();
+        Self {
+            entries,
+            total_bytes,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // 打印删除计划，不会触碰磁盘
+    pub fn print(&self) {
+        if self.is_empty() {
+            println!("[DRY RUN] 没有需要删除的目录");
+            return;
+        }
+        println!("[DRY RUN] 以下目录将被删除:");
+        print_entries_table(&self.entries);
+        println!("{}", self.summary());
+    }
+
+    // 以机器可读格式输出删除计划：条目写到 stdout，汇总写到 stderr，不影响管道中的解析
+    pub fn write(&self, format: OutputFormat) -> io::Result<()> {
+        output::write_entries(&mut io::stdout().lock(), format, &self.entries)?;
+        eprintln!("{}", self.summary());
+        Ok(())
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "[DRY RUN] 可释放空间: {} ({} bytes)，未删除任何文件",
+            human_readable_size(self.total_bytes),
+            self.total_bytes
+        )
+    }
+}
@@ -0,0 +1,139 @@
+// 删除前的安全检查：每次删除之前（而不只是扫描时）都会重新检查，
+// 拒绝删除根目录、家目录、系统目录、受保护的路径和扫描根目录之外的路径。
+// 符号链接按链接本身所在的位置检查，删除时只移除链接，不会影响它指向的目录
+use crate::utils::{canonicalize_parent, expand_tilde, home_dir};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub struct Protection {
+    // 规范化后的扫描根目录，删除目标必须位于其中
+    root: PathBuf,
+    home: Option<PathBuf>,
+    protect: GlobSet,
+}
+
+impl Protection {
+    // patterns 为用户配置的受保护路径 glob，"~/" 开头时相对于家目录
+    pub fn new(scan_root: &Path, patterns: &[String]) -> Result<Self, globset::Error> {
+        let home = home_dir();
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            builder.add(Glob::new(&expand_tilde(pattern))?);
+        }
+        Ok(Self {
+            root: scan_root
+                .canonicalize()
+                .unwrap_or_else(|_| scan_root.to_path_buf()),
+            home: home.map(|home| home.canonicalize().unwrap_or(home)),
+            protect: builder.build()?,
+        })
+    }
+
+    // 允许删除时返回 Ok，否则返回拒绝原因
+    pub fn check(&self, path: &Path) -> Result<(), String> {
+        fs::symlink_metadata(path).map_err(|e| e.to_string())?;
+        // 规范化上级目录后再比较，路径中间的符号链接可能指向扫描根目录之外
+        let canonical = canonicalize_parent(path).map_err(|e| e.to_string())?;
+        if canonical == self.root || !canonical.starts_with(&self.root) {
+            return Err(format!(
+                "拒绝删除扫描根目录之外的路径: {}",
+                canonical.display()
+            ));
+        }
+        if canonical.parent().is_none() {
+            return Err("拒绝删除文件系统根目录".to_string());
+        }
+        if self
+            .home
+            .as_ref()
+            .is_some_and(|home| home.starts_with(&canonical))
+        {
+            return Err(format!(
+                "拒绝删除家目录或其上级目录: {}",
+                canonical.display()
+            ));
+        }
+        if system_dirs().iter().any(|dir| canonical.starts_with(dir)) {
+            return Err(format!("拒绝删除系统目录中的路径: {}", canonical.display()));
+        }
+        // 受保护目录的子目录同样受保护
+        if canonical.ancestors().any(|p| self.protect.is_match(p)) {
+            return Err(format!("路径受保护: {}", canonical.display()));
+        }
+        Ok(())
+    }
+}
+
+// 系统目录：其中的任何路径都不允许删除
+#[cfg(unix)]
+fn system_dirs() -> Vec<PathBuf> {
+    [
+        "/bin", "/boot", "/dev", "/etc", "/lib", "/lib32", "/lib64", "/proc", "/sbin", "/sys",
+        "/usr", "/System", "/Library",
+    ]
+    .iter()
+    .map(PathBuf::from)
+    .collect()
+}
+
+#[cfg(not(unix))]
+fn system_dirs() -> Vec<PathBuf> {
+    [
+        "SystemRoot",
+        "ProgramFiles",
+        "ProgramFiles(x86)",
+        "ProgramData",
+    ]
+    .iter()
+    .filter_map(std::env::var_os)
+    .map(PathBuf::from)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_paths_outside_root_and_protected_globs() {
+        let root = std::env::temp_dir().join(format!("rust_kill-protect-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("app/node_modules")).unwrap();
+        fs::create_dir_all(root.join("legacy/node_modules")).unwrap();
+        let root = root.canonicalize().unwrap();
+        let patterns = vec![format!("{}/legacy", root.display())];
+        let protection = Protection::new(&root.join("app"), &[]).unwrap();
+        let protected = Protection::new(&root, &patterns).unwrap();
+
+        assert!(protection.check(&root.join("app/node_modules")).is_ok());
+        assert!(protection.check(&root.join("legacy/node_modules")).is_err());
+        assert!(protection.check(&root.join("app")).is_err());
+        assert!(protected.check(&root.join("app/node_modules")).is_ok());
+        assert!(protected.check(&root.join("legacy/node_modules")).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn checks_symlinks_by_their_own_location() {
+        let root = std::env::temp_dir().join(format!("rust_kill-link-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("app")).unwrap();
+        fs::create_dir_all(root.join("shared/node_modules")).unwrap();
+        let root = root.canonicalize().unwrap();
+        std::os::unix::fs::symlink(
+            root.join("shared/node_modules"),
+            root.join("app/node_modules"),
+        )
+        .unwrap();
+        let protection = Protection::new(&root.join("app"), &[]).unwrap();
+
+        // 链接位于扫描根目录内，即使指向根目录之外也允许删除（只删除链接）
+        assert!(protection.check(&root.join("app/node_modules")).is_ok());
+        assert!(protection.check(&root.join("shared/node_modules")).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,217 @@
+// 隔离区：删除时把目录改名移动到同一文件系统上的暂存目录，记录到清单中，
+// 超过保留期后再真正删除；在此之前可以通过 `rust_kill restore` 或 TUI 的 u 键恢复
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::utils::canonicalize_parent;
+#[cfg(unix)]
+use crate::utils::{current_uid, find_mount_root};
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+
+// 清单的读-改-写需要串行化，TUI 会在多个线程中同时隔离
+static MANIFEST_LOCK: Mutex<()> = Mutex::new(());
+static ID_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineRecord {
+    pub id: String,
+    pub original_path: PathBuf,
+    pub staged_path: PathBuf,
+    pub quarantined_at: u64, // UNIX 时间戳（秒）
+}
+
+impl QuarantineRecord {
+    pub fn quarantined_time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.quarantined_at)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    records: Vec<QuarantineRecord>,
+}
+
+// 把 path 移入隔离区，返回隔离记录
+pub fn quarantine(path: &Path) -> io::Result<QuarantineRecord> {
+    // 只规范化上级目录：path 是符号链接时移动的是链接本身
+    let path = canonicalize_parent(path)?;
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "无法隔离: 没有文件名"))?
+        .to_string_lossy()
+        .into_owned();
+
+    let staging = staging_dir_for(&path)?;
+    fs::create_dir_all(&staging)?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    // 时间戳 + 进程号 + 计数器，避免并发的多个进程生成相同的 id
+    let id = format!(
+        "{:x}{:04x}{:04x}",
+        now.as_secs(),
+        std::process::id() & 0xffff,
+        ID_COUNTER.fetch_add(1, Ordering::SeqCst) & 0xffff
+    );
+    let staged_path = staging.join(format!("{}-{}", id, name));
+    // 只做改名，跨文件系统时直接失败而不是复制
+    fs::rename(&path, &staged_path)?;
+
+    let record = QuarantineRecord {
+        id,
+        original_path: path,
+        staged_path,
+        quarantined_at: now.as_secs(),
+    };
+    let _guard = MANIFEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut manifest = load_manifest()?;
+    manifest.records.push(record.clone());
+    if let Err(e) = save_manifest(&manifest) {
+        // 清单写入失败时撤销移动，避免出现无法恢复的孤儿目录
+        let _ = fs::rename(&record.staged_path, &record.original_path);
+        return Err(e);
+    }
+    Ok(record)
+}
+
+// 按 id、原路径或暂存路径恢复
+pub fn restore(target: &str) -> io::Result<QuarantineRecord> {
+    let _guard = MANIFEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut manifest = load_manifest()?;
+    let target_path = Path::new(target);
+    let target_canonical = absolute_path(target_path);
+    let index = manifest
+        .records
+        .iter()
+        .rposition(|r| {
+            r.id == target
+                || r.original_path == target_path
+                || r.original_path == target_canonical
+                || r.staged_path == target_path
+        })
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("隔离区中没有找到: {}", target),
+            )
+        })?;
+
+    let record = manifest.records[index].clone();
+    if fs::symlink_metadata(&record.original_path).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("原路径已存在: {}", record.original_path.display()),
+        ));
+    }
+    if let Some(parent) = record.original_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(&record.staged_path, &record.original_path)?;
+
+    manifest.records.remove(index);
+    save_manifest(&manifest)?;
+    Ok(record)
+}
+
+// 列出隔离区中的全部记录
+pub fn list() -> io::Result<Vec<QuarantineRecord>> {
+    let _guard = MANIFEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(load_manifest()?.records)
+}
+
+// 删除超过保留期的隔离项，返回被清除的记录
+pub fn purge_expired(retention: Duration) -> io::Result<Vec<QuarantineRecord>> {
+    let _guard = MANIFEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut manifest = load_manifest()?;
+    let now = SystemTime::now();
+    let (expired, kept): (Vec<_>, Vec<_>) = manifest.records.into_iter().partition(|r| {
+        now.duration_since(r.quarantined_time())
+            .map(|age| age >= retention)
+            .unwrap_or(false)
+    });
+    if expired.is_empty() {
+        return Ok(expired);
+    }
+
+    let mut purged = Vec::new();
+    let mut remaining = kept;
+    for record in expired {
+        match fs::remove_dir_all(&record.staged_path) {
+            Ok(_) => purged.push(record),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => purged.push(record),
+            Err(e) => {
+                log::info!("清除隔离项失败: {:?}: {}", record.staged_path, e);
+                remaining.push(record);
+            }
+        }
+    }
+    manifest.records = remaining;
+    save_manifest(&manifest)?;
+    Ok(purged)
+}
+
+// rust_kill 的数据目录：$XDG_DATA_HOME/rust_kill，默认 ~/.local/share/rust_kill
+fn data_dir() -> io::Result<PathBuf> {
+    if let Some(data_home) = std::env::var_os("XDG_DATA_HOME").filter(|v| !v.is_empty()) {
+        return Ok(PathBuf::from(data_home).join("rust_kill"));
+    }
+    let home = std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "未设置 HOME 环境变量"))?;
+    Ok(PathBuf::from(home).join(".local/share/rust_kill"))
+}
+
+fn manifest_path() -> io::Result<PathBuf> {
+    Ok(data_dir()?.join("quarantine.json"))
+}
+
+fn load_manifest() -> io::Result<Manifest> {
+    match fs::read(manifest_path()?) {
+        Ok(bytes) => serde_json::from_slice(&bytes).map_err(io::Error::other),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Manifest::default()),
+        Err(e) => Err(e),
+    }
+}
+
+// 先写临时文件再改名，保证清单不会写坏
+fn save_manifest(manifest: &Manifest) -> io::Result<()> {
+    let path = manifest_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(
+        &tmp,
+        serde_json::to_vec_pretty(manifest).map_err(io::Error::other)?,
+    )?;
+    fs::rename(tmp, path)
+}
+
+// 暂存目录必须与被隔离的目录位于同一文件系统，这样改名是瞬时的
+#[cfg(unix)]
+fn staging_dir_for(path: &Path) -> io::Result<PathBuf> {
+    let home_staging = data_dir()?.join("quarantine");
+    fs::create_dir_all(&home_staging)?;
+    if fs::metadata(&home_staging)?.dev() == fs::symlink_metadata(path)?.dev() {
+        return Ok(home_staging);
+    }
+    let topdir = find_mount_root(path)?;
+    Ok(topdir.join(format!(".rust_kill-quarantine-{}", current_uid())))
+}
+
+#[cfg(not(unix))]
+fn staging_dir_for(_path: &Path) -> io::Result<PathBuf> {
+    Ok(data_dir()?.join("quarantine"))
+}
+
+// 原路径此时已不存在，只能规范化其父目录
+fn absolute_path(path: &Path) -> PathBuf {
+    canonicalize_parent(path).unwrap_or_else(|_| path.to_path_buf())
+}
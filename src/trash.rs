@@ -0,0 +1,164 @@
+// 按 freedesktop.org Trash 规范把目录移入回收站
+// https://specifications.freedesktop.org/trash-spec/latest/
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[cfg(unix)]
+use crate::utils::{canonicalize_parent, current_uid, find_mount_root};
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+
+// 把 path 移入回收站，返回回收站中的新路径
+#[cfg(unix)]
+pub fn move_to_trash(path: &Path) -> io::Result<PathBuf> {
+    // 只规范化上级目录：path 是符号链接时移动的是链接本身
+    let path = canonicalize_parent(path)?;
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "无法移入回收站: 没有文件名"))?
+        .to_string_lossy()
+        .into_owned();
+
+    let dev = fs::symlink_metadata(&path)?.dev();
+    let home_trash = home_trash_dir()?;
+    create_trash_dirs(&home_trash)?;
+
+    // 与家目录回收站同一设备时使用家目录回收站，否则使用挂载点下的回收站
+    let (trash_dir, info_path) = if fs::metadata(&home_trash)?.dev() == dev {
+        (home_trash, path.clone())
+    } else {
+        let topdir = find_mount_root(&path)?;
+        let trash_dir = topdir_trash_dir(&topdir)?;
+        create_trash_dirs(&trash_dir)?;
+        let relative = path
+            .strip_prefix(&topdir)
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|_| path.clone());
+        (trash_dir, relative)
+    };
+
+    // 先以独占方式创建 .trashinfo 占住名字，再移动文件
+    let (trash_name, mut info_file) = reserve_info_file(&trash_dir, &name)?;
+    let info = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        url_encode_path(&info_path),
+        chrono::Local::now().format("%Y-%m-%dT%H:%M:%S")
+    );
+    let info_file_path = trash_dir
+        .join("info")
+        .join(format!("{}.trashinfo", trash_name));
+    if let Err(e) = io::Write::write_all(&mut info_file, info.as_bytes()) {
+        let _ = fs::remove_file(&info_file_path);
+        return Err(e);
+    }
+
+    let target = trash_dir.join("files").join(&trash_name);
+    if let Err(e) = fs::rename(&path, &target) {
+        let _ = fs::remove_file(&info_file_path);
+        return Err(e);
+    }
+    Ok(target)
+}
+
+#[cfg(not(unix))]
+pub fn move_to_trash(_path: &Path) -> io::Result<PathBuf> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "当前平台不支持 freedesktop 回收站",
+    ))
+}
+
+// $XDG_DATA_HOME/Trash，默认 ~/.local/share/Trash
+#[cfg(unix)]
+fn home_trash_dir() -> io::Result<PathBuf> {
+    if let Some(data_home) = std::env::var_os("XDG_DATA_HOME").filter(|v| !v.is_empty()) {
+        return Ok(PathBuf::from(data_home).join("Trash"));
+    }
+    let home = std::env::var_os("HOME")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "未设置 HOME 环境变量"))?;
+    Ok(PathBuf::from(home).join(".local/share/Trash"))
+}
+
+// 挂载点回收站：优先 $topdir/.Trash/$uid（要求 .Trash 设置了粘滞位且不是符号链接），
+// 否则使用 $topdir/.Trash-$uid
+#[cfg(unix)]
+fn topdir_trash_dir(topdir: &Path) -> io::Result<PathBuf> {
+    let uid = current_uid();
+    let shared = topdir.join(".Trash");
+    if let Ok(meta) = fs::symlink_metadata(&shared) {
+        let sticky = meta.permissions().mode() & 0o1000 != 0;
+        if meta.is_dir() && !meta.file_type().is_symlink() && sticky {
+            let dir = shared.join(uid.to_string());
+            if create_trash_dirs(&dir).is_ok() {
+                return Ok(dir);
+            }
+        }
+    }
+    Ok(topdir.join(format!(".Trash-{}", uid)))
+}
+
+#[cfg(unix)]
+fn create_trash_dirs(trash_dir: &Path) -> io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true).mode(0o700);
+    builder.create(trash_dir.join("files"))?;
+    builder.create(trash_dir.join("info"))
+}
+
+// 找到一个在 info/ 中未被占用的名字，并以 O_EXCL 创建对应的 .trashinfo
+#[cfg(unix)]
+fn reserve_info_file(trash_dir: &Path, name: &str) -> io::Result<(String, fs::File)> {
+    for i in 0.. {
+        let candidate = if i == 0 {
+            name.to_string()
+        } else {
+            format!("{}.{}", name, i)
+        };
+        // files/ 中有同名项（例如孤立文件）也要跳过
+        if fs::symlink_metadata(trash_dir.join("files").join(&candidate)).is_ok() {
+            continue;
+        }
+        let info_path = trash_dir
+            .join("info")
+            .join(format!("{}.trashinfo", candidate));
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&info_path)
+        {
+            Ok(file) => return Ok((candidate, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!()
+}
+
+// 按 RFC 2396 对路径做百分号编码，保留 '/'
+fn url_encode_path(path: &Path) -> String {
+    let mut encoded = String::new();
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_encode_path_keeps_slashes() {
+        assert_eq!(
+            url_encode_path(Path::new("/home/u/my proj/node_modules")),
+            "/home/u/my%20proj/node_modules"
+        );
+        assert_eq!(url_encode_path(Path::new("a/中")), "a/%E4%B8%AD");
+    }
+}
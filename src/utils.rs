@@ -38,3 +38,185 @@ pub fn progress_bar_init(
     pb.set_style(style.progress_chars("#>-"));
     Ok(pb)
 }
+
+// 当前用户的 uid
+#[cfg(unix)]
+pub fn current_uid() -> u32 {
+    // SAFETY: getuid 总是成功，且没有副作用
+    unsafe { libc::getuid() }
+}
+
+// 查找路径所在文件系统的挂载点（与 path 位于同一设备的最上层祖先目录）
+#[cfg(unix)]
+pub fn find_mount_root(path: &std::path::Path) -> std::io::Result<std::path::PathBuf> {
+    use std::os::unix::fs::MetadataExt;
+
+    let dev = std::fs::symlink_metadata(path)?.dev();
+    let mut root = path.to_path_buf();
+    for ancestor in path.ancestors().skip(1) {
+        match std::fs::metadata(ancestor) {
+            Ok(m) if m.dev() == dev => root = ancestor.to_path_buf(),
+            _ => break,
+        }
+    }
+    Ok(root)
+}
+
+// 当前用户的家目录
+pub fn home_dir() -> Option<std::path::PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(std::path::PathBuf::from)
+}
+
+// 规范化父目录并保留最后一段：path 本身是符号链接时得到的是链接所在的位置，
+// 而不是它指向的目录
+pub fn canonicalize_parent(path: &std::path::Path) -> std::io::Result<std::path::PathBuf> {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => {
+            let parent = if parent.as_os_str().is_empty() {
+                std::path::Path::new(".")
+            } else {
+                parent
+            };
+            Ok(parent.canonicalize()?.join(name))
+        }
+        _ => path.canonicalize(),
+    }
+}
+
+// 展开 glob 开头的 "~/"，用于配置文件和命令行中的路径模式
+pub fn expand_tilde(pattern: &str) -> String {
+    match (pattern.strip_prefix("~/"), home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest).to_string_lossy().into_owned(),
+        _ => pattern.to_string(),
+    }
+}
+
+// 解析 "30d" / "12h" / "2w" 这样的时长，无单位时按天计算
+pub fn parse_duration(input: &str) -> Result<std::time::Duration, String> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+    let value: u64 = number
+        .parse()
+        .map_err(|_| format!("无效的时长: {:?}", input))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" | "min" => 60,
+        "h" => 60 * 60,
+        "" | "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        "mo" => 30 * 24 * 60 * 60,
+        "y" => 365 * 24 * 60 * 60,
+        _ => return Err(format!("无效的时长单位: {:?}（可用 s/m/h/d/w/mo/y）", unit)),
+    };
+    value
+        .checked_mul(seconds)
+        .map(std::time::Duration::from_secs)
+        .ok_or_else(|| format!("时长过长: {:?}", input))
+}
+
+// 把时间格式化为相对时长，如 "5m"、"3d"、"7mo"
+pub fn format_age(time: std::time::SystemTime) -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(time)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+    match secs {
+        s if s < MINUTE => "now".to_string(),
+        s if s < HOUR => format!("{}m", s / MINUTE),
+        s if s < DAY => format!("{}h", s / HOUR),
+        s if s < 30 * DAY => format!("{}d", s / DAY),
+        s if s < 365 * DAY => format!("{}mo", s / (30 * DAY)),
+        s => format!("{}y", s / (365 * DAY)),
+    }
+}
+
+// 保留字符串末尾，使其显示宽度不超过 width 列，被截断时以 "..." 开头；
+// 按字符截断（非 ASCII 路径也安全），中文等宽字符按 2 列计算
+pub fn truncate_start(s: &str, width: usize) -> String {
+    use unicode_width::UnicodeWidthChar;
+    if unicode_width::UnicodeWidthStr::width(s) <= width {
+        return s.to_string();
+    }
+    if width < 3 {
+        return ".".repeat(width);
+    }
+    let mut tail = Vec::new();
+    let mut used = 3;
+    for c in s.chars().rev() {
+        used += c.width().unwrap_or(0);
+        if used > width {
+            break;
+        }
+        tail.push(c);
+    }
+    format!("...{}", tail.iter().rev().collect::<String>())
+}
+
+// 模糊匹配：needle 的每个字符按顺序出现在 haystack 中即可（忽略大小写）
+pub fn fuzzy_match(haystack: &str, needle: &str) -> bool {
+    let mut chars = haystack.chars().flat_map(char::to_lowercase);
+    needle
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|n| chars.any(|h| h == n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn parse_duration_units() {
+        assert_eq!(parse_duration("30d"), Ok(Duration::from_secs(30 * 86400)));
+        assert_eq!(parse_duration("12h"), Ok(Duration::from_secs(12 * 3600)));
+        assert_eq!(parse_duration("2w"), Ok(Duration::from_secs(14 * 86400)));
+        assert_eq!(parse_duration("7"), Ok(Duration::from_secs(7 * 86400)));
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("3x").is_err());
+        assert!(parse_duration("99999999999999999y").is_err());
+    }
+
+    #[test]
+    fn truncate_start_by_display_width() {
+        assert_eq!(truncate_start("/tmp/app", 20), "/tmp/app");
+        assert_eq!(truncate_start("/tmp/app/node_modules", 10), "...modules");
+        assert_eq!(truncate_start("/tmp/项目/node_modules", 8), "...dules");
+        assert_eq!(truncate_start("/tmp/项目", 8), ".../项目");
+        assert_eq!(truncate_start("/tmp/项目", 7), "...项目");
+        assert_eq!(truncate_start("/tmp/项目", 6), "...目");
+        assert_eq!(truncate_start("/tmp/app", 2), "..");
+        assert_eq!(truncate_start("/tmp/app", 0), "");
+    }
+
+    #[test]
+    fn format_age_buckets() {
+        let now = std::time::SystemTime::now();
+        assert_eq!(format_age(now), "now");
+        assert_eq!(format_age(now - Duration::from_secs(3 * 86400 + 10)), "3d");
+        assert_eq!(
+            format_age(now - Duration::from_secs(7 * 30 * 86400 + 10)),
+            "7mo"
+        );
+        assert_eq!(
+            format_age(now - Duration::from_secs(2 * 365 * 86400 + 10)),
+            "2y"
+        );
+    }
+
+    #[test]
+    fn fuzzy_match_subsequence() {
+        assert!(fuzzy_match("/home/u/web-app/node_modules", "webnm"));
+        assert!(fuzzy_match("/home/u/Web-App/target", "WEBAPP"));
+        assert!(!fuzzy_match("/home/u/api/target", "app"));
+        assert!(fuzzy_match("anything", ""));
+    }
+}